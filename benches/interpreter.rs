@@ -0,0 +1,46 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use chip8_core::Chip8;
+
+// a busy loop of arithmetic, skips, memory access and drawing, standing in for a game's main loop
+const PROGRAM: [u8; 28] = [
+    0x60, 0x00, // 0x200: LD V0, 0x00
+    0x61, 0x00, // 0x202: LD V1, 0x00
+    0xA2, 0x1A, // 0x204: LD I, 0x21a
+    0x70, 0x01, // 0x206: ADD V0, 0x01
+    0x81, 0x04, // 0x208: ADD V1, V0
+    0x82, 0x10, // 0x20a: LD V2, V1
+    0x82, 0x06, // 0x20c: SHR V2, V0
+    0xF2, 0x33, // 0x20e: LD B, V2
+    0xF2, 0x65, // 0x210: LD V2, [I]
+    0xD0, 0x11, // 0x212: DRW V0, V1, 1
+    0x30, 0xFF, // 0x214: SE V0, 0xff
+    0x12, 0x06, // 0x216: JP 0x206
+    0x12, 0x00, // 0x218: JP 0x200
+    0x00, 0x00, // 0x21a: scratch
+];
+
+const STEPS: usize = 10_000;
+
+fn interpreter(c: &mut Criterion) {
+    step(c, "step 10000 instructions", true);
+    // every opcode is decoded as it runs, to show what the instruction cache saves
+    step(c, "step 10000 instructions uncached", false);
+}
+
+fn step(c: &mut Criterion, name: &str, instruction_cache: bool) {
+    let mut chip8 = Chip8::new(0);
+    chip8.set_instruction_cache(instruction_cache);
+    chip8.load(&PROGRAM);
+    c.bench_function(name, |b| {
+        b.iter(|| {
+            for _ in 0..STEPS {
+                chip8.step();
+            }
+            black_box(chip8.registers().v[0]);
+        })
+    });
+}
+
+criterion_group!(benches, interpreter);
+criterion_main!(benches);
@@ -0,0 +1,28 @@
+fn main() {
+    #[cfg(feature = "capi")]
+    generate_header();
+}
+
+// writes the C header for the `capi` feature into the build's output directory, and over
+// include/chip8_core.h when CHIP8_CORE_WRITE_HEADER is set, so builds leave the checkout clean
+#[cfg(feature = "capi")]
+fn generate_header() {
+    use std::path::Path;
+
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").expect("manifest directory not set");
+    let out_dir = std::env::var("OUT_DIR").expect("output directory not set");
+    println!("cargo:rerun-if-changed=src/capi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-env-changed=CHIP8_CORE_WRITE_HEADER");
+    let config = cbindgen::Config::from_root_or_default(&crate_dir);
+    // only the C API is parsed, so other foreign functions such as the libretro core stay out
+    let bindings = cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(Path::new(&crate_dir).join("src/capi.rs"))
+        .generate()
+        .expect("failed to generate the C header");
+    bindings.write_to_file(Path::new(&out_dir).join("chip8_core.h"));
+    if std::env::var_os("CHIP8_CORE_WRITE_HEADER").is_some() {
+        bindings.write_to_file(Path::new(&crate_dir).join("include/chip8_core.h"));
+    }
+}
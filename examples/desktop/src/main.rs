@@ -1,4 +1,4 @@
-use chip8_core::{Chip8, Key, KeyState, FRAME_HEIGHT, FRAME_WIDTH};
+use chip8_core::{Chip8, KeyState, FRAME_HEIGHT, FRAME_WIDTH};
 
 use pixels::{Error, Pixels, SurfaceTexture};
 use winit::{
@@ -50,36 +50,13 @@ fn main() -> Result<(), Error> {
                 event: WindowEvent::KeyboardInput { input, .. },
                 window_id,
             } if window_id == window.id() => {
-                let key_code = match input.virtual_keycode {
-                    Some(key) => match key {
-                        VirtualKeyCode::Key1 => Some(Key::Key1),
-                        VirtualKeyCode::Key2 => Some(Key::Key2),
-                        VirtualKeyCode::Key3 => Some(Key::Key3),
-                        VirtualKeyCode::Key4 => Some(Key::KeyC),
-                        VirtualKeyCode::Q => Some(Key::Key4),
-                        VirtualKeyCode::W => Some(Key::Key5),
-                        VirtualKeyCode::E => Some(Key::Key6),
-                        VirtualKeyCode::R => Some(Key::KeyD),
-                        VirtualKeyCode::A => Some(Key::Key7),
-                        VirtualKeyCode::S => Some(Key::Key8),
-                        VirtualKeyCode::D => Some(Key::Key9),
-                        VirtualKeyCode::F => Some(Key::KeyE),
-                        VirtualKeyCode::Z => Some(Key::KeyA),
-                        VirtualKeyCode::X => Some(Key::Key0),
-                        VirtualKeyCode::C => Some(Key::KeyB),
-                        VirtualKeyCode::V => Some(Key::KeyF),
-                        _ => None,
-                    },
-                    None => None,
-                };
                 let key_state = match input.state {
                     ElementState::Pressed => KeyState::Pressed,
                     ElementState::Released => KeyState::Released,
                 };
 
-                match key_code {
-                    Some(key) => chip8.handle_key_event(key, key_state),
-                    None => (),
+                if let Some(key) = input.virtual_keycode {
+                    chip8.handle_host_key_event(&host_key(key), key_state);
                 }
             }
 
@@ -95,3 +72,13 @@ fn main() -> Result<(), Error> {
         }
     });
 }
+
+// converts a winit key code into the label used by the chip8 key map,
+// winit names the number row keys `Key1`, `Key2`... so we strip the prefix
+fn host_key(key: VirtualKeyCode) -> String {
+    let name = format!("{:?}", key);
+    match name.strip_prefix("Key") {
+        Some(digit) => digit.to_string(),
+        None => name,
+    }
+}
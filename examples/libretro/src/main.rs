@@ -0,0 +1,153 @@
+use std::cell::RefCell;
+use std::ffi::{c_uint, c_void};
+use std::mem::MaybeUninit;
+use std::{env, fs, process, ptr, slice};
+
+use chip8_core::libretro::*;
+
+const RETRO_ENVIRONMENT_SET_PIXEL_FORMAT: c_uint = 10;
+const DEFAULT_FRAMES: u32 = 600;
+
+#[derive(Default)]
+struct Frontend {
+    frame: Vec<u32>,
+    width: usize,
+    height: usize,
+    audio_frames: usize,
+    loud_audio_frames: usize,
+}
+
+thread_local! {
+    static FRONTEND: RefCell<Frontend> = RefCell::new(Frontend::default());
+}
+
+// accepts the pixel format and leaves every core option on its default
+unsafe extern "C" fn environment(cmd: c_uint, _data: *mut c_void) -> bool {
+    cmd == RETRO_ENVIRONMENT_SET_PIXEL_FORMAT
+}
+
+unsafe extern "C" fn video_refresh(
+    data: *const c_void,
+    width: c_uint,
+    height: c_uint,
+    pitch: usize,
+) {
+    let (width, height) = (width as usize, height as usize);
+    let rows = slice::from_raw_parts(data as *const u8, pitch * height);
+    FRONTEND.with_borrow_mut(|frontend| {
+        frontend.width = width;
+        frontend.height = height;
+        frontend.frame = rows
+            .chunks_exact(pitch)
+            .flat_map(|row| {
+                row[..width * 4]
+                    .chunks_exact(4)
+                    .map(|pixel| u32::from_ne_bytes(pixel.try_into().unwrap()))
+            })
+            .collect();
+    });
+}
+
+unsafe extern "C" fn audio_sample(_left: i16, _right: i16) {}
+
+unsafe extern "C" fn audio_sample_batch(data: *const i16, frames: usize) -> usize {
+    let samples = slice::from_raw_parts(data, frames * 2);
+    FRONTEND.with_borrow_mut(|frontend| {
+        frontend.audio_frames += frames;
+        frontend.loud_audio_frames += samples.chunks_exact(2).filter(|s| s[0] != 0).count();
+    });
+    frames
+}
+
+unsafe extern "C" fn input_poll() {}
+
+unsafe extern "C" fn input_state(
+    _port: c_uint,
+    _device: c_uint,
+    _index: c_uint,
+    _id: c_uint,
+) -> i16 {
+    0
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let Some(path) = args.get(1) else {
+        eprintln!("usage: {} <PATH> [FRAMES]", args[0]);
+        process::exit(1);
+    };
+    let frames = args
+        .get(2)
+        .and_then(|frames| frames.parse().ok())
+        .unwrap_or(DEFAULT_FRAMES);
+    let program = fs::read(path).expect("failed to read the program");
+
+    unsafe {
+        retro_set_environment(environment);
+        retro_set_video_refresh(video_refresh);
+        retro_set_audio_sample(audio_sample);
+        retro_set_audio_sample_batch(audio_sample_batch);
+        retro_set_input_poll(input_poll);
+        retro_set_input_state(input_state);
+        retro_init();
+
+        let mut av_info = MaybeUninit::uninit();
+        retro_get_system_av_info(av_info.as_mut_ptr());
+        let av_info = av_info.assume_init();
+
+        let game = RetroGameInfo {
+            path: ptr::null(),
+            data: program.as_ptr() as *const c_void,
+            size: program.len(),
+            meta: ptr::null(),
+        };
+        if !retro_load_game(&game) {
+            eprintln!("the core could not load {}", path);
+            process::exit(1);
+        }
+
+        for _ in 0..frames {
+            retro_run();
+        }
+
+        // a state saved now should bring back the same frame after running on
+        let mut state = vec![0u8; retro_serialize_size()];
+        let saved = retro_serialize(state.as_mut_ptr() as *mut c_void, state.len());
+        retro_run();
+        let expected = FRONTEND.with_borrow(|frontend| frontend.frame.clone());
+        let restored = retro_unserialize(state.as_ptr() as *const c_void, state.len());
+        retro_run();
+        let matches = FRONTEND.with_borrow(|frontend| frontend.frame == expected);
+
+        FRONTEND.with_borrow(|frontend| {
+            for row in frontend.frame.chunks_exact(frontend.width) {
+                let line: String = row
+                    .iter()
+                    .map(|&pixel| if pixel == 0 { ' ' } else { '#' })
+                    .collect();
+                println!("{}", line);
+            }
+            println!(
+                "{} frames of {}x{} at {} fps",
+                frames + 2,
+                frontend.width,
+                frontend.height,
+                av_info.timing.fps
+            );
+            println!(
+                "{} audio frames at {} Hz, {} with the buzzer sounding",
+                frontend.audio_frames, av_info.timing.sample_rate, frontend.loud_audio_frames
+            );
+        });
+        println!(
+            "save state of {} bytes: saved {}, restored {}, frame matches {}",
+            state.len(),
+            saved,
+            restored,
+            matches
+        );
+
+        retro_unload_game();
+        retro_deinit();
+    }
+}
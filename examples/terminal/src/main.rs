@@ -0,0 +1,193 @@
+use chip8_core::{Chip8, Key, KeyState, Rom, RunState, FRAME_HEIGHT, FRAME_WIDTH};
+
+use crossterm::{
+    cursor::{Hide, MoveTo, Show},
+    event::{
+        self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, KeyboardEnhancementFlags,
+        PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
+    execute, queue,
+    style::{Attribute, Print, SetAttribute},
+    terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+
+use std::{
+    env, fs,
+    io::{self, Write},
+    path::Path,
+    process,
+    time::{Duration, Instant},
+};
+
+use rand::{thread_rng, RngCore};
+
+const FRAME_DURATION: Duration = Duration::from_micros(1_000_000 / 60);
+
+// without release events a key is held until it stops repeating
+const KEY_HOLD_DURATION: Duration = Duration::from_millis(150);
+
+fn main() -> io::Result<()> {
+    let mut args = env::args().skip(1);
+    let path = args.next().expect("usage: terminal <PATH> [--flash]");
+    let flash = args.any(|arg| arg == "--flash");
+
+    let mut chip8 = Chip8::new(thread_rng().next_u32());
+    let bytes = fs::read(Path::new(&path))?;
+    if let Err(error) = Rom::new(&bytes).and_then(|rom| chip8.load_rom(&rom)) {
+        eprintln!("{}: {}", path, error);
+        process::exit(1);
+    }
+    chip8.set_key_wait_fast_path(true);
+
+    let terminal = Terminal::enter()?;
+    let result = run(&mut chip8, &terminal, flash);
+    drop(terminal);
+    result
+}
+
+fn run(chip8: &mut Chip8, terminal: &Terminal, flash: bool) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    let mut held: [Option<Instant>; 16] = [None; 16];
+    let mut sounding = false;
+    let mut previous_instant = Instant::now();
+    let mut previous_rows = Vec::new();
+
+    loop {
+        let deadline = previous_instant + FRAME_DURATION;
+        while event::poll(deadline.saturating_duration_since(Instant::now()))? {
+            if let Event::Key(event) = event::read()? {
+                if is_quit(&event) {
+                    return Ok(());
+                }
+                handle_key(chip8, &mut held, &event);
+            }
+        }
+
+        if !terminal.reports_releases {
+            for (key, pressed_at) in held.iter_mut().enumerate() {
+                if pressed_at.is_some_and(|instant| instant.elapsed() >= KEY_HOLD_DURATION) {
+                    *pressed_at = None;
+                    chip8.handle_key_event(Key::from(key as u8), KeyState::Released);
+                }
+            }
+        }
+
+        let time_elapsed = previous_instant.elapsed().as_micros();
+        previous_instant = Instant::now();
+        chip8.update(time_elapsed as u32);
+
+        let was_sounding = sounding;
+        sounding = chip8.registers().st > 0;
+        if sounding && !was_sounding && !flash {
+            queue!(stdout, Print('\u{7}'))?;
+        }
+
+        let mut rows = render(chip8, flash && sounding);
+        rows.push(status(chip8));
+        for (y, row) in rows.iter().enumerate() {
+            if previous_rows.get(y) != Some(row) {
+                queue!(stdout, MoveTo(0, y as u16), Print(row))?;
+            }
+        }
+        previous_rows = rows;
+        stdout.flush()?;
+    }
+}
+
+// each cell covers two rows of pixels, the upper half block draws the top one
+// and the lower half block the bottom one
+fn render(chip8: &Chip8, inverted: bool) -> Vec<String> {
+    let frame = chip8.frame();
+    (0..FRAME_HEIGHT)
+        .step_by(2)
+        .map(|y| {
+            (0..FRAME_WIDTH)
+                .map(|x| {
+                    let top = frame.is_pixel_on(x, y) != inverted;
+                    let bottom = frame.is_pixel_on(x, y + 1) != inverted;
+                    match (top, bottom) {
+                        (true, true) => '█',
+                        (true, false) => '▀',
+                        (false, true) => '▄',
+                        (false, false) => ' ',
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// shown on the line below the display
+fn status(chip8: &Chip8) -> String {
+    let status = match chip8.run_state() {
+        RunState::Halted => "finished, press esc to quit",
+        RunState::Error => "crashed, press esc to quit",
+        _ if chip8.is_waiting_for_key() => "press a key",
+        _ => "",
+    };
+    format!("{:<width$}", status, width = FRAME_WIDTH)
+}
+
+fn handle_key(chip8: &mut Chip8, held: &mut [Option<Instant>; 16], event: &KeyEvent) {
+    let KeyCode::Char(character) = event.code else {
+        return;
+    };
+    let Some(key) = chip8.key_map().get(&character.to_lowercase().to_string()) else {
+        return;
+    };
+    let index = u8::from(key) as usize;
+    match event.kind {
+        KeyEventKind::Release => {
+            held[index] = None;
+            chip8.handle_key_event(key, KeyState::Released);
+        }
+        KeyEventKind::Press | KeyEventKind::Repeat => {
+            if held[index].is_none() {
+                chip8.handle_key_event(key, KeyState::Pressed);
+            }
+            held[index] = Some(Instant::now());
+        }
+    }
+}
+
+fn is_quit(event: &KeyEvent) -> bool {
+    event.code == KeyCode::Esc
+        || (event.code == KeyCode::Char('c') && event.modifiers.contains(KeyModifiers::CONTROL))
+}
+
+// puts the terminal into raw mode on the alternate screen, restoring it when dropped
+struct Terminal {
+    reports_releases: bool,
+}
+
+impl Terminal {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        execute!(stdout, EnterAlternateScreen, Hide, Clear(ClearType::All))?;
+        let reports_releases = terminal::supports_keyboard_enhancement().unwrap_or(false);
+        if reports_releases {
+            execute!(
+                stdout,
+                PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
+            )?;
+        }
+        Ok(Self { reports_releases })
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let mut stdout = io::stdout();
+        if self.reports_releases {
+            let _ = execute!(stdout, PopKeyboardEnhancementFlags);
+        }
+        let _ = execute!(
+            stdout,
+            SetAttribute(Attribute::Reset),
+            Show,
+            LeaveAlternateScreen
+        );
+        let _ = terminal::disable_raw_mode();
+    }
+}
@@ -1,4 +1,4 @@
-import { Chip8, Key, KeyState } from "../pkg/chip8_core";
+import { Chip8, KeyState } from "../pkg/chip8_core";
 
 async function run() {
   // this creates a random unsigned 32bit number
@@ -12,24 +12,8 @@ async function run() {
   // we need to keep track of the previous timestamp so we can calculate the elapsed time
   let previousTimeStamp;
 
-  const keys = {
-    1: Key.Key1,
-    2: Key.Key2,
-    3: Key.Key3,
-    4: Key.KeyC,
-    q: Key.Key4,
-    w: Key.Key5,
-    e: Key.Key6,
-    r: Key.KeyD,
-    a: Key.Key7,
-    s: Key.Key8,
-    d: Key.Key9,
-    f: Key.KeyE,
-    z: Key.KeyA,
-    x: Key.Key0,
-    c: Key.KeyB,
-    v: Key.KeyF,
-  };
+  // numpad keys share their labels with the number row, so we identify them by their code
+  const hostKey = (e) => (e.code.startsWith("Numpad") ? e.code : e.key);
 
   const input = document.querySelector("#input");
   input.addEventListener("change", (e) => {
@@ -75,14 +59,10 @@ async function run() {
       });
       console.log(chip8.registers());
     }
-    const key = keys[e.key];
-    const keyState = KeyState.Pressed;
-    chip8.handle_key_event(key, keyState);
+    chip8.handle_host_key_event(hostKey(e), KeyState.Pressed);
   });
   window.addEventListener("keyup", (e) => {
-    const key = keys[e.key];
-    const keyState = KeyState.Released;
-    chip8.handle_key_event(key, keyState);
+    chip8.handle_host_key_event(hostKey(e), KeyState.Released);
   });
 }
 
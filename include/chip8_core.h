@@ -0,0 +1,181 @@
+/* Generated by cbindgen from src/capi.rs, do not edit it by hand. */
+
+#ifndef CHIP8_CORE_H
+#define CHIP8_CORE_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Struct representing the Chip-8 virtual machine.
+ */
+typedef struct Chip8 Chip8;
+
+/**
+ * The width of the frame in pixels.
+ */
+#define CHIP8_FRAME_WIDTH 64
+
+/**
+ * The height of the frame in pixels.
+ */
+#define CHIP8_FRAME_HEIGHT 32
+
+/**
+ * The size of the frame buffer in bytes, four per pixel.
+ */
+#define CHIP8_FRAME_SIZE 8192
+
+/**
+ * The result of a call into the library.
+ */
+typedef enum Chip8Status {
+  CHIP8_STATUS_OK = 0,
+  /**
+   * A required pointer was null.
+   */
+  CHIP8_STATUS_NULL_POINTER = 1,
+  /**
+   * An argument was out of range, such as a key above 0xF.
+   */
+  CHIP8_STATUS_INVALID_ARGUMENT = 2,
+  /**
+   * The program was empty or too large for the platform.
+   */
+  CHIP8_STATUS_INVALID_ROM = 3,
+  /**
+   * The saved state was malformed.
+   */
+  CHIP8_STATUS_INVALID_STATE = 4,
+  /**
+   * The buffer given was too small, the size needed has been written out.
+   */
+  CHIP8_STATUS_BUFFER_TOO_SMALL = 5,
+  /**
+   * The virtual machine panicked.
+   */
+  CHIP8_STATUS_PANIC = 6,
+} Chip8Status;
+
+/**
+ * The registers of the virtual machine.
+ */
+typedef struct Chip8Registers {
+  uint16_t pc;
+  uint16_t i;
+  uint8_t sp;
+  uint8_t dt;
+  uint8_t st;
+  uint8_t v[16];
+} Chip8Registers;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Creates a virtual machine, which must be released with `chip8_free`.
+ */
+Chip8 *chip8_new(uint32_t seed);
+
+/**
+ * Releases a virtual machine created by `chip8_new`. Null is ignored.
+ *
+ * # Safety
+ * `chip8` must be null or a pointer returned by `chip8_new` that hasn't been released.
+ */
+void chip8_free(Chip8 *chip8);
+
+/**
+ * Validates and loads a program, after clearing any previous program.
+ *
+ * # Safety
+ * `chip8` must be a live virtual machine and `bytes` must point to `len` readable bytes.
+ */
+enum Chip8Status chip8_load(Chip8 *chip8, const uint8_t *bytes, size_t len);
+
+/**
+ * Resets the virtual machine, clearing the loaded program.
+ *
+ * # Safety
+ * `chip8` must be a live virtual machine.
+ */
+enum Chip8Status chip8_reset(Chip8 *chip8);
+
+/**
+ * Progresses the virtual machine by the given time delta in microseconds.
+ *
+ * # Safety
+ * `chip8` must be a live virtual machine.
+ */
+enum Chip8Status chip8_update(Chip8 *chip8, uint32_t time_delta);
+
+/**
+ * Executes a single cycle of the virtual machine.
+ *
+ * # Safety
+ * `chip8` must be a live virtual machine.
+ */
+enum Chip8Status chip8_step(Chip8 *chip8);
+
+/**
+ * Presses or releases a key, from 0x0 to 0xF.
+ *
+ * # Safety
+ * `chip8` must be a live virtual machine.
+ */
+enum Chip8Status chip8_key_event(Chip8 *chip8, uint8_t key, bool pressed);
+
+/**
+ * Returns the frame as `CHIP8_FRAME_SIZE` bytes of RGBA, row by row, or null if `chip8` is null.
+ * The pointer stays valid until the virtual machine is next changed or released.
+ *
+ * # Safety
+ * `chip8` must be null or a live virtual machine.
+ */
+const uint8_t *chip8_frame(const Chip8 *chip8);
+
+/**
+ * Writes the registers to `registers`.
+ *
+ * # Safety
+ * `chip8` must be a live virtual machine and `registers` must be writable.
+ */
+enum Chip8Status chip8_get_registers(Chip8 *chip8, struct Chip8Registers *registers);
+
+/**
+ * Sets the registers from `registers`.
+ *
+ * # Safety
+ * `chip8` must be a live virtual machine and `registers` must be readable.
+ */
+enum Chip8Status chip8_set_registers(Chip8 *chip8, const struct Chip8Registers *registers);
+
+/**
+ * Saves the state of the virtual machine into `buffer`, writing its size to `size`.
+ * If the buffer is too small nothing is copied, and `size` tells how large it needs to be;
+ * a null buffer with a capacity of zero can be used to query the size.
+ *
+ * # Safety
+ * `chip8` must be a live virtual machine, `buffer` must point to `capacity` writable bytes
+ * and `size` must be writable.
+ */
+enum Chip8Status chip8_save_state(Chip8 *chip8, uint8_t *buffer, size_t capacity, size_t *size);
+
+/**
+ * Restores a state saved with `chip8_save_state`.
+ * The virtual machine is left untouched if the state is invalid.
+ *
+ * # Safety
+ * `chip8` must be a live virtual machine and `bytes` must point to `len` readable bytes.
+ */
+enum Chip8Status chip8_load_state(Chip8 *chip8, const uint8_t *bytes, size_t len);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CHIP8_CORE_H */
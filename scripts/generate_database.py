@@ -0,0 +1,128 @@
+#!/usr/bin/env python3
+"""Generates src/database/entries.rs from the chip-8-database.
+
+Usage: generate_database.py <path to chip-8-database/database>
+
+Only programs for platforms this library can run are included (the original
+VIP interpreter, modern CHIP-8 and SUPER-CHIP).
+"""
+
+import json
+import sys
+from pathlib import Path
+
+PLATFORMS = {
+    "originalChip8": "Platform::Vip",
+    "hybridVIP": "Platform::Vip",
+    "modernChip8": "Platform::Modern",
+    "chip48": "Platform::Schip",
+    "superchip1": "Platform::Schip",
+    "superchip": "Platform::Schip",
+}
+
+OUTPUT = Path(__file__).resolve().parent.parent / "src" / "database" / "entries.rs"
+
+HEADER = """\
+// This file is generated by `scripts/generate_database.py` from the chip-8-database
+// (https://github.com/chip-8/chip-8-database), do not edit it by hand.
+// Entries must stay ordered by hash, since lookups use a binary search.
+
+#[allow(unused_imports)]
+use super::Entry;
+#[allow(unused_imports)]
+use crate::keypad::Key;
+#[allow(unused_imports)]
+use crate::platform::Platform;
+#[allow(unused_imports)]
+use crate::quirks::Quirks;
+
+"""
+
+
+def rust_string(value):
+    return json.dumps(value, ensure_ascii=False)
+
+
+def rust_color(value):
+    value = value.lstrip("#")
+    channels = [int(value[i : i + 2], 16) for i in (0, 2, 4)]
+    return "[0x{:02x}, 0x{:02x}, 0x{:02x}, 0xff]".format(*channels)
+
+
+def rust_quirks(quirks):
+    return (
+        "Quirks {{ shift: {}, load_store: {}, clip: {}, vblank: {}, jump: {}, logic: {} }}".format(
+            str(quirks.get("shift", False)).lower(),
+            str(quirks.get("memoryLeaveIUnchanged", False)).lower(),
+            str(not quirks.get("wrap", False)).lower(),
+            str(quirks.get("vblank", False)).lower(),
+            str(quirks.get("jump", False)).lower(),
+            str(quirks.get("logic", False)).lower(),
+        )
+    )
+
+
+def entry(sha1, program, rom, platform_quirks):
+    platform_id = next(p for p in rom["platforms"] if p in PLATFORMS)
+    quirks = dict(platform_quirks[platform_id])
+    quirks.update(rom.get("quirkyPlatforms", {}).get(platform_id, {}))
+
+    authors = program.get("authors") or []
+    author = "Some({})".format(rust_string(", ".join(authors))) if authors else "None"
+    tick_rate = "Some({})".format(rom["tickrate"]) if "tickrate" in rom else "None"
+
+    pixels = rom.get("colors", {}).get("pixels", [])
+    colors = "None"
+    if len(pixels) >= 2:
+        colors = "Some(({}, {}))".format(rust_color(pixels[1]), rust_color(pixels[0]))
+
+    hints = ", ".join(
+        "({}, Key::Key{:X})".format(rust_string(name), key)
+        for name, key in sorted(rom.get("keys", {}).items())
+    )
+
+    return """\
+    Entry {{
+        sha1: [{}],
+        title: {},
+        author: {},
+        platform: {},
+        quirks: {},
+        tick_rate: {},
+        colors: {},
+        key_hints: &[{}],
+    }},
+""".format(
+        ", ".join("0x" + sha1[i : i + 2] for i in range(0, 40, 2)),
+        rust_string(program["title"]),
+        author,
+        PLATFORMS[platform_id],
+        rust_quirks(quirks),
+        tick_rate,
+        colors,
+        hints,
+    )
+
+
+def main():
+    database = Path(sys.argv[1])
+    programs = json.loads((database / "programs.json").read_text())
+    platforms = json.loads((database / "platforms.json").read_text())
+    platform_quirks = {platform["id"]: platform["quirks"] for platform in platforms}
+
+    entries = {}
+    for program in programs:
+        for sha1, rom in program["roms"].items():
+            if any(p in PLATFORMS for p in rom["platforms"]):
+                entries[sha1.lower()] = entry(sha1.lower(), program, rom, platform_quirks)
+
+    with OUTPUT.open("w") as output:
+        output.write(HEADER)
+        output.write("#[rustfmt::skip]\npub static ENTRIES: &[Entry] = &[\n")
+        for sha1 in sorted(entries):
+            output.write(entries[sha1])
+        output.write("];\n")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,295 @@
+//! C bindings for the virtual machine, behind the `capi` feature.
+//!
+//! The virtual machine is handed out as an opaque pointer created by `chip8_new` and released by
+//! `chip8_free`. Every other function returns a `Chip8Status`, and panics are caught at the boundary
+//! and reported as `CHIP8_STATUS_PANIC`, after which the virtual machine should be reset.
+
+use alloc::boxed::Box;
+use core::slice;
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::cpu::registers::Registers;
+use crate::frame::{FRAME_HEIGHT, FRAME_WIDTH};
+use crate::keypad::{Key, KeyState};
+use crate::rom::Rom;
+use crate::Chip8;
+
+// spelt out so that they appear as numbers in the header
+/// The width of the frame in pixels.
+pub const CHIP8_FRAME_WIDTH: usize = 64;
+/// The height of the frame in pixels.
+pub const CHIP8_FRAME_HEIGHT: usize = 32;
+/// The size of the frame buffer in bytes, four per pixel.
+pub const CHIP8_FRAME_SIZE: usize = 8192;
+
+const _: () = assert!(CHIP8_FRAME_WIDTH == FRAME_WIDTH && CHIP8_FRAME_HEIGHT == FRAME_HEIGHT);
+const _: () = assert!(CHIP8_FRAME_SIZE == FRAME_WIDTH * FRAME_HEIGHT * 4);
+
+/// The result of a call into the library.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Chip8Status {
+    Ok = 0,
+    /// A required pointer was null.
+    NullPointer = 1,
+    /// An argument was out of range, such as a key above 0xF.
+    InvalidArgument = 2,
+    /// The program was empty or too large for the platform.
+    InvalidRom = 3,
+    /// The saved state was malformed.
+    InvalidState = 4,
+    /// The buffer given was too small, the size needed has been written out.
+    BufferTooSmall = 5,
+    /// The virtual machine panicked.
+    Panic = 6,
+}
+
+/// The registers of the virtual machine.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct Chip8Registers {
+    pub pc: u16,
+    pub i: u16,
+    pub sp: u8,
+    pub dt: u8,
+    pub st: u8,
+    pub v: [u8; 16],
+}
+
+/// Creates a virtual machine, which must be released with `chip8_free`.
+#[no_mangle]
+pub extern "C" fn chip8_new(seed: u32) -> *mut Chip8 {
+    Box::into_raw(Box::new(Chip8::new(seed)))
+}
+
+/// Releases a virtual machine created by `chip8_new`. Null is ignored.
+///
+/// # Safety
+/// `chip8` must be null or a pointer returned by `chip8_new` that hasn't been released.
+#[no_mangle]
+pub unsafe extern "C" fn chip8_free(chip8: *mut Chip8) {
+    if !chip8.is_null() {
+        drop(Box::from_raw(chip8));
+    }
+}
+
+/// Validates and loads a program, after clearing any previous program.
+///
+/// # Safety
+/// `chip8` must be a live virtual machine and `bytes` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn chip8_load(
+    chip8: *mut Chip8,
+    bytes: *const u8,
+    len: usize,
+) -> Chip8Status {
+    if bytes.is_null() {
+        return Chip8Status::NullPointer;
+    }
+    let bytes = slice::from_raw_parts(bytes, len);
+    with_chip8(chip8, |chip8| {
+        let rom = match Rom::new(bytes) {
+            Ok(rom) => rom,
+            Err(_) => return Chip8Status::InvalidRom,
+        };
+        match chip8.load_rom(&rom) {
+            Ok(()) => Chip8Status::Ok,
+            Err(_) => Chip8Status::InvalidRom,
+        }
+    })
+}
+
+/// Resets the virtual machine, clearing the loaded program.
+///
+/// # Safety
+/// `chip8` must be a live virtual machine.
+#[no_mangle]
+pub unsafe extern "C" fn chip8_reset(chip8: *mut Chip8) -> Chip8Status {
+    with_chip8(chip8, |chip8| {
+        chip8.reset();
+        Chip8Status::Ok
+    })
+}
+
+/// Progresses the virtual machine by the given time delta in microseconds.
+///
+/// # Safety
+/// `chip8` must be a live virtual machine.
+#[no_mangle]
+pub unsafe extern "C" fn chip8_update(chip8: *mut Chip8, time_delta: u32) -> Chip8Status {
+    with_chip8(chip8, |chip8| {
+        chip8.update(time_delta);
+        Chip8Status::Ok
+    })
+}
+
+/// Executes a single cycle of the virtual machine.
+///
+/// # Safety
+/// `chip8` must be a live virtual machine.
+#[no_mangle]
+pub unsafe extern "C" fn chip8_step(chip8: *mut Chip8) -> Chip8Status {
+    with_chip8(chip8, |chip8| {
+        chip8.step();
+        Chip8Status::Ok
+    })
+}
+
+/// Presses or releases a key, from 0x0 to 0xF.
+///
+/// # Safety
+/// `chip8` must be a live virtual machine.
+#[no_mangle]
+pub unsafe extern "C" fn chip8_key_event(chip8: *mut Chip8, key: u8, pressed: bool) -> Chip8Status {
+    if key > 0xF {
+        return Chip8Status::InvalidArgument;
+    }
+    let state = if pressed {
+        KeyState::Pressed
+    } else {
+        KeyState::Released
+    };
+    with_chip8(chip8, |chip8| {
+        chip8.handle_key_event(Key::from(key), state);
+        Chip8Status::Ok
+    })
+}
+
+/// Returns the frame as `CHIP8_FRAME_SIZE` bytes of RGBA, row by row, or null if `chip8` is null.
+/// The pointer stays valid until the virtual machine is next changed or released.
+///
+/// # Safety
+/// `chip8` must be null or a live virtual machine.
+#[no_mangle]
+pub unsafe extern "C" fn chip8_frame(chip8: *const Chip8) -> *const u8 {
+    match chip8.as_ref() {
+        Some(chip8) => chip8.cpu.frame.buffer.as_ptr(),
+        None => core::ptr::null(),
+    }
+}
+
+/// Writes the registers to `registers`.
+///
+/// # Safety
+/// `chip8` must be a live virtual machine and `registers` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn chip8_get_registers(
+    chip8: *mut Chip8,
+    registers: *mut Chip8Registers,
+) -> Chip8Status {
+    if registers.is_null() {
+        return Chip8Status::NullPointer;
+    }
+    with_chip8(chip8, |chip8| {
+        let Registers {
+            pc,
+            i,
+            sp,
+            dt,
+            st,
+            v,
+        } = chip8.registers();
+        registers.write(Chip8Registers {
+            pc,
+            i,
+            sp,
+            dt,
+            st,
+            v,
+        });
+        Chip8Status::Ok
+    })
+}
+
+/// Sets the registers from `registers`.
+///
+/// # Safety
+/// `chip8` must be a live virtual machine and `registers` must be readable.
+#[no_mangle]
+pub unsafe extern "C" fn chip8_set_registers(
+    chip8: *mut Chip8,
+    registers: *const Chip8Registers,
+) -> Chip8Status {
+    let Some(&Chip8Registers {
+        pc,
+        i,
+        sp,
+        dt,
+        st,
+        v,
+    }) = registers.as_ref()
+    else {
+        return Chip8Status::NullPointer;
+    };
+    with_chip8(chip8, |chip8| {
+        chip8.set_registers(Registers {
+            pc,
+            i,
+            sp,
+            dt,
+            st,
+            v,
+        });
+        Chip8Status::Ok
+    })
+}
+
+/// Saves the state of the virtual machine into `buffer`, writing its size to `size`.
+/// If the buffer is too small nothing is copied, and `size` tells how large it needs to be;
+/// a null buffer with a capacity of zero can be used to query the size.
+///
+/// # Safety
+/// `chip8` must be a live virtual machine, `buffer` must point to `capacity` writable bytes
+/// and `size` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn chip8_save_state(
+    chip8: *mut Chip8,
+    buffer: *mut u8,
+    capacity: usize,
+    size: *mut usize,
+) -> Chip8Status {
+    if size.is_null() {
+        return Chip8Status::NullPointer;
+    }
+    with_chip8(chip8, |chip8| {
+        let state = chip8.save_state();
+        size.write(state.len());
+        if state.len() > capacity {
+            return Chip8Status::BufferTooSmall;
+        }
+        if buffer.is_null() {
+            return Chip8Status::NullPointer;
+        }
+        slice::from_raw_parts_mut(buffer, state.len()).copy_from_slice(&state);
+        Chip8Status::Ok
+    })
+}
+
+/// Restores a state saved with `chip8_save_state`.
+/// The virtual machine is left untouched if the state is invalid.
+///
+/// # Safety
+/// `chip8` must be a live virtual machine and `bytes` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn chip8_load_state(
+    chip8: *mut Chip8,
+    bytes: *const u8,
+    len: usize,
+) -> Chip8Status {
+    if bytes.is_null() {
+        return Chip8Status::NullPointer;
+    }
+    let bytes = slice::from_raw_parts(bytes, len);
+    with_chip8(chip8, |chip8| match chip8.load_state(bytes) {
+        Ok(()) => Chip8Status::Ok,
+        Err(_) => Chip8Status::InvalidState,
+    })
+}
+
+// checks the handle and turns panics into a status, so they never unwind into the host
+unsafe fn with_chip8(chip8: *mut Chip8, f: impl FnOnce(&mut Chip8) -> Chip8Status) -> Chip8Status {
+    let Some(chip8) = chip8.as_mut() else {
+        return Chip8Status::NullPointer;
+    };
+    panic::catch_unwind(AssertUnwindSafe(|| f(chip8))).unwrap_or(Chip8Status::Panic)
+}
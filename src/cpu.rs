@@ -1,25 +1,43 @@
 use alloc::borrow::ToOwned;
-use nanorand::{Rng, WyRand};
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::format;
+use alloc::string::{String, ToString};
 
+use crate::event::Event;
+use crate::font::{Font, BIG_GLYPH_SIZE, SMALL_FONT_SIZE, SMALL_GLYPH_SIZE};
 use crate::frame::FrameBuffer;
 use crate::keypad::{Key, KeyPad, KeyState};
-use font::{FONT, FONT_CHAR_SIZE};
+use crate::layout::{MemoryFill, MemoryLayout, MEMORY_SIZE};
+use crate::platform::Platform;
+use crate::quirks::Quirks;
+use crate::random::{RandomSource, WyRandSource};
+use crate::speed::{check_speed, SpeedError};
+use coverage::Coverage;
 use instructions::Instruction;
+use journal::WriteJournal;
 use memory::Memory;
+use profiler::Profiler;
 use registers::Registers;
 
-mod font;
+pub mod coverage;
 mod instructions;
+pub mod journal;
 mod memory;
+pub mod profiler;
 pub mod registers;
+pub mod state;
 
-const STACK_SIZE: usize = 16;
+// the stack pointer is a byte, which bounds the stack when the platform doesn't; the stack
+// is always this size, 510 bytes, whatever the depth, so that calls never allocate
+pub(crate) const STACK_SIZE: usize = u8::MAX as usize;
 const OPCODE_SIZE: u16 = 2;
-const FONT_START_OFFSET: usize = 0;
 const ONE_SECOND_IN_MICRO_SECONDS: u32 = 1_000_000;
 const DEFAULT_INSTRUCTIONS_PER_SECOND: u32 = 700;
 const TIMER_STEP_THRESHOLD_MICRO_SECONDS: u32 = 16_666;
-const PROGRAM_START: usize = 0x200;
+// the oldest events are dropped beyond this, for hosts that don't collect them
+const EVENT_CAPACITY: usize = 1024;
+const IDLE_LOOP_LENGTH: u32 = 3;
 
 enum ProgramCounterStatus {
     Repeat,
@@ -28,15 +46,34 @@ enum ProgramCounterStatus {
     Jump(u16),
 }
 
+// a loop polling the delay timer into VX, left once the skip on VX and NN is taken
+struct IdleLoop {
+    x: u8,
+    nn: u8,
+    exit_when_equal: bool,
+}
+
+impl IdleLoop {
+    fn exits(&self, dt: u8) -> bool {
+        (dt == self.nn) == self.exit_when_equal
+    }
+}
+
 enum Timer {
     Delay,
     Sound,
 }
 
 pub struct Cpu {
-    rng: WyRand,
+    pub rng: Box<dyn RandomSource>,
     cpu_time_accumulator: u32,
     pub instructions_per_second: u32,
+    pub platform: Platform,
+    pub quirks: Quirks,
+    pub layout: MemoryLayout,
+    // the most subroutine calls that can be nested, up to the size of the stack
+    pub stack_depth: Option<u8>,
+    pub font: Font,
     micro_seconds_per_instruction: u32,
     pub registers: Registers,
     stack: [u16; STACK_SIZE],
@@ -45,14 +82,31 @@ pub struct Cpu {
     pub key_pad: KeyPad,
     st_time_accumulator: u32,
     dt_time_accumulator: u32,
+    frame_time_accumulator: u32,
+    frames: u32,
+    vblank_ready: bool,
+    waiting_for_key: bool,
+    // the key pressed while FX0A waits, which it takes once released
+    key_wait_key: Option<Key>,
+    pub key_wait_fast_path: bool,
+    faulted: bool,
+    pub events: VecDeque<Event>,
+    pub profiler: Option<Profiler>,
+    pub coverage: Option<Coverage>,
+    pub journal: Option<WriteJournal>,
 }
 
 impl Cpu {
     pub fn new(seed: u32) -> Self {
         let mut cpu = Self {
-            rng: WyRand::new_seed(seed.into()),
+            rng: Box::new(WyRandSource::new(seed.into())),
             cpu_time_accumulator: 0,
             instructions_per_second: 0,
+            platform: Platform::default(),
+            quirks: Quirks::default(),
+            layout: MemoryLayout::default(),
+            stack_depth: Platform::default().stack_depth(),
+            font: Font::default(),
             micro_seconds_per_instruction: 0,
             registers: Registers::new(),
             stack: [0; STACK_SIZE],
@@ -61,53 +115,271 @@ impl Cpu {
             key_pad: KeyPad::new(),
             st_time_accumulator: 0,
             dt_time_accumulator: 0,
+            frame_time_accumulator: 0,
+            frames: 0,
+            vblank_ready: false,
+            waiting_for_key: false,
+            key_wait_key: None,
+            key_wait_fast_path: false,
+            faulted: false,
+            events: VecDeque::new(),
+            profiler: None,
+            coverage: None,
+            journal: None,
         };
 
-        cpu.set_speed(DEFAULT_INSTRUCTIONS_PER_SECOND);
-        cpu.ram.load(FONT_START_OFFSET, FONT.as_slice());
+        cpu.set_speed(DEFAULT_INSTRUCTIONS_PER_SECOND)
+            .expect("the default speed is in range");
+        cpu.initialize_memory();
         cpu
     }
 
     pub fn load_program(&mut self, bytes: &[u8]) {
-        self.ram.load(PROGRAM_START, bytes);
+        self.ram.load(self.layout.program_start as usize, bytes);
+    }
+
+    pub fn clear_program(&mut self) {
+        let program_start = self.layout.program_start as usize;
+        self.fill_memory(program_start, MEMORY_SIZE - program_start);
     }
 
     pub fn reset(&mut self) {
         self.cpu_time_accumulator = 0;
         self.registers = Registers::new();
         self.stack = [0; STACK_SIZE];
-        self.ram = Memory::new();
+        self.initialize_memory();
         self.dt_time_accumulator = 0;
         self.st_time_accumulator = 0;
+        self.frame_time_accumulator = 0;
+        self.vblank_ready = false;
+        self.waiting_for_key = false;
+        self.key_wait_key = None;
+        self.faulted = false;
+        self.events.clear();
         self.frame.clear();
-        self.ram.load(FONT_START_OFFSET, FONT.as_slice());
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.clear_calls();
+        }
+        if let Some(journal) = self.journal.as_mut() {
+            journal.clear_executed();
+        }
+    }
+
+    fn initialize_memory(&mut self) {
+        self.fill_memory(0, MEMORY_SIZE);
+        self.load_font();
+        self.registers.pc = self.layout.program_start;
     }
 
-    pub fn set_speed(&mut self, instructions_per_second: u32) {
+    pub fn load_font(&mut self) {
+        let font_address = self.layout.font_address as usize;
+        self.ram.load(font_address, &self.font.small);
+        if let Some(big) = self.font.big {
+            self.ram.load(font_address + SMALL_FONT_SIZE, &big);
+        }
+    }
+
+    fn fill_memory(&mut self, offset: usize, size: usize) {
+        match self.layout.fill {
+            MemoryFill::Zero => self.ram.fill(offset, size, 0),
+            MemoryFill::Pattern(byte) => self.ram.fill(offset, size, byte),
+            MemoryFill::Random => {
+                for address in offset..offset + size {
+                    self.ram.load(address, &[self.rng.next_byte()]);
+                }
+            }
+        }
+    }
+
+    pub fn set_speed(&mut self, instructions_per_second: u32) -> Result<(), SpeedError> {
+        check_speed(instructions_per_second)?;
         self.instructions_per_second = instructions_per_second;
         self.micro_seconds_per_instruction = ONE_SECOND_IN_MICRO_SECONDS / instructions_per_second;
+        Ok(())
     }
 
-    pub fn update(&mut self, time_delta: u32) {
-        let total_time_accumulated = self.cpu_time_accumulator + time_delta;
+    pub fn micro_seconds_per_instruction(&self) -> u32 {
+        self.micro_seconds_per_instruction
+    }
+
+    /// Accumulates the time delta and returns how many instructions are now due to be emulated.
+    pub fn instructions_due(&mut self, time_delta: u32) -> u32 {
+        let total_time_accumulated = self.cpu_time_accumulator.saturating_add(time_delta);
         let instructions_to_emulate = total_time_accumulated / self.micro_seconds_per_instruction;
-        for _ in 0..instructions_to_emulate {
-            self.step();
-        }
         let time_progressed = instructions_to_emulate * self.micro_seconds_per_instruction;
         self.cpu_time_accumulator = total_time_accumulated - time_progressed;
+        instructions_to_emulate
     }
 
     pub fn step(&mut self) {
-        self.step_instruction();
+        let was_sounding = self.registers.st > 0;
+        // re-running FX0A changes nothing until a key is released, so the fast path skips it
+        let idle = self.key_wait_fast_path
+            && self.is_waiting_for_key()
+            && self.key_wait_result().is_none();
+        if !self.faulted && !idle {
+            self.step_instruction();
+        }
+        self.tick(was_sounding);
+    }
+
+    // everything in a cycle besides the instruction
+    fn tick(&mut self, was_sounding: bool) {
         self.step_timer(Timer::Delay);
         self.step_timer(Timer::Sound);
+        self.step_vblank();
         self.key_pad.reset_released_keys();
+
+        match (was_sounding, self.registers.st > 0) {
+            (false, true) => self.emit(Event::SoundStarted),
+            (true, false) => self.emit(Event::SoundStopped),
+            _ => (),
+        }
+    }
+
+    /// Skips whole iterations of a loop polling the delay timer, `FX07` then `3XNN` or `4XNN`
+    /// then a jump back to the `FX07`, ticking the timers as if it had run, for up to the given
+    /// number of instructions and until the loop would see the timer change.
+    /// Returns the number of instructions skipped, none if the program isn't at such a loop.
+    /// Nothing is skipped while profiling, tracking coverage or journaling writes, which need
+    /// to see every instruction.
+    pub fn skip_idle_loop(&mut self, max_instructions: u32) -> u32 {
+        if self.faulted
+            || self.profiler.is_some()
+            || self.coverage.is_some()
+            || self.journal.is_some()
+        {
+            return 0;
+        }
+        let Some(idle_loop) = self.idle_loop() else {
+            return 0;
+        };
+        let mut skipped = 0;
+        while skipped + IDLE_LOOP_LENGTH <= max_instructions
+            && self.registers.dt > 0
+            && !idle_loop.exits(self.registers.dt)
+        {
+            // each iteration leaves the timer's value in VX and the program counter at the FX07
+            self.registers.v[idle_loop.x as usize] = self.registers.dt;
+            for _ in 0..IDLE_LOOP_LENGTH {
+                self.tick(self.registers.st > 0);
+            }
+            skipped += IDLE_LOOP_LENGTH;
+        }
+        skipped
+    }
+
+    fn idle_loop(&mut self) -> Option<IdleLoop> {
+        let pc = self.registers.pc as usize;
+        let Instruction::OpCodeFX07(x) = self.ram.instruction(pc)? else {
+            return None;
+        };
+        let idle_loop = match self.ram.instruction(pc + OPCODE_SIZE as usize)? {
+            Instruction::OpCode3XNN(skip_x, nn) if skip_x == x => IdleLoop {
+                x,
+                nn,
+                exit_when_equal: true,
+            },
+            Instruction::OpCode4XNN(skip_x, nn) if skip_x == x => IdleLoop {
+                x,
+                nn,
+                exit_when_equal: false,
+            },
+            _ => return None,
+        };
+        match self.ram.instruction(pc + OPCODE_SIZE as usize * 2)? {
+            Instruction::OpCode1NNN(nnn) if nnn as usize == pc => Some(idle_loop),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the program has faulted, see `Event::Fault`.
+    pub fn is_faulted(&self) -> bool {
+        self.faulted
+    }
+
+    /// Sets the state of a key. A key pressed while `FX0A` waits becomes the one it waits on
+    /// to be released, so that keys are taken in the order they're pressed.
+    pub fn apply_key_event(&mut self, key: Key, state: KeyState) {
+        if state == KeyState::Pressed
+            && self.key_wait_key.is_none()
+            && self.key_pad.get(key) != KeyState::Pressed
+            && self.is_waiting_for_key()
+        {
+            self.key_wait_key = Some(key);
+        }
+        self.key_pad.set(key, state);
+    }
+
+    // like the VIP, FX0A waits for a key to be pressed and then for that key to be released
+    fn key_wait_result(&self) -> Option<Key> {
+        self.key_wait_key
+            .filter(|&key| self.key_pad.get(key) == KeyState::Released)
+    }
+
+    /// Returns whether `FX0A` is waiting for a key to be pressed and released.
+    pub fn is_waiting_for_key(&self) -> bool {
+        const FX0A_MASK: u16 = 0xf0ff;
+        const FX0A: u16 = 0xf00a;
+        let pc = self.registers.pc as usize;
+        // the flag goes stale if the program counter is moved from under it
+        self.waiting_for_key
+            && !self.faulted
+            && self
+                .ram
+                .data()
+                .get(pc..pc + OPCODE_SIZE as usize)
+                .is_some_and(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]) & FX0A_MASK == FX0A)
+    }
+
+    /// Returns the number of 60 Hz frames emulated, wrapping around.
+    pub fn frames(&self) -> u32 {
+        self.frames
+    }
+
+    /// Returns whether the program has finished: it is at a jump to itself, the conventional
+    /// end of a program, and the timers have run out, so nothing can change anymore.
+    pub fn is_finished(&mut self) -> bool {
+        let pc = self.registers.pc;
+        !self.faulted
+            && self.registers.dt == 0
+            && self.registers.st == 0
+            && self.ram.instruction(pc as usize) == Some(Instruction::OpCode1NNN(pc))
+    }
+
+    fn fault(&mut self) {
+        if !self.faulted {
+            self.faulted = true;
+            self.emit(Event::Fault);
+        }
+    }
+
+    pub fn emit(&mut self, event: Event) {
+        if self.events.len() == EVENT_CAPACITY {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
     }
 
     fn step_instruction(&mut self) {
-        let opcode = self.fetch();
-        let instruction: Instruction = opcode.into();
+        let Some(instruction) = self
+            .ram
+            .instruction(self.registers.pc as usize)
+            .filter(|instruction| self.is_supported(instruction))
+        else {
+            self.fault();
+            return;
+        };
+
+        let key_wait =
+            matches!(instruction, Instruction::OpCodeFX0A(_)) && self.key_wait_result().is_none();
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.record(self.registers.pc, &instruction, self.registers.dt, key_wait);
+        }
+        self.mark_coverage(self.registers.pc, OPCODE_SIZE as usize, Coverage::EXECUTED);
+        if let Some(journal) = self.journal.as_mut() {
+            journal.mark_executed(self.registers.pc, OPCODE_SIZE as usize);
+        }
 
         match self.execute(instruction) {
             ProgramCounterStatus::Repeat => (),
@@ -117,6 +389,27 @@ impl Cpu {
         }
     }
 
+    fn fits_at_index(&self, size: usize) -> bool {
+        self.registers.i as usize + size <= MEMORY_SIZE
+    }
+
+    // reaching past the end of memory faults rather than wrapping around
+    fn index_out_of_bounds(&mut self) -> ProgramCounterStatus {
+        self.emit(Event::MemoryOutOfBounds {
+            address: self.registers.i,
+        });
+        self.fault();
+        ProgramCounterStatus::Repeat
+    }
+
+    // SUPER-CHIP's instructions are invalid opcodes on the other platforms
+    fn is_supported(&self, instruction: &Instruction) -> bool {
+        match instruction {
+            Instruction::OpCodeFX30(_) => self.platform == Platform::Schip,
+            _ => true,
+        }
+    }
+
     fn step_timer(&mut self, timer: Timer) {
         let (register, accumulator) = match timer {
             Timer::Delay => (&mut self.registers.dt, &mut self.dt_time_accumulator),
@@ -136,13 +429,55 @@ impl Cpu {
         }
     }
 
-    fn fetch(&self) -> u16 {
-        u16::from_be_bytes(
-            self.ram
-                .read(self.registers.pc as usize, 2)
-                .try_into()
-                .expect("failed to fetch instruction"),
-        )
+    fn step_vblank(&mut self) {
+        let accumulated_time = self.frame_time_accumulator + self.micro_seconds_per_instruction;
+        if accumulated_time >= TIMER_STEP_THRESHOLD_MICRO_SECONDS {
+            self.frame_time_accumulator = accumulated_time - TIMER_STEP_THRESHOLD_MICRO_SECONDS;
+            self.frames = self.frames.wrapping_add(1);
+            self.vblank_ready = true;
+        } else {
+            self.frame_time_accumulator = accumulated_time;
+        }
+    }
+
+    fn mark_coverage(&mut self, address: u16, size: usize, flag: u8) {
+        if let Some(coverage) = self.coverage.as_mut() {
+            coverage.mark(address, size, flag);
+        }
+    }
+
+    // every write to memory made by an instruction goes through here, so it can be journaled
+    fn store(&mut self, address: u16, bytes: &[u8]) {
+        self.mark_coverage(address, bytes.len(), Coverage::WRITTEN);
+        if let Some(journal) = self.journal.as_mut() {
+            let old = self.ram.read(address as usize, bytes.len());
+            journal.record(self.registers.pc, address, old, bytes);
+        }
+        self.ram.load(address as usize, bytes);
+    }
+
+    /// Returns the addresses on the call stack, oldest first.
+    pub fn stack(&self) -> &[u16] {
+        &self.stack[..self.registers.sp as usize]
+    }
+
+    fn is_stack_full(&self) -> bool {
+        let depth = self.stack_depth.map_or(STACK_SIZE, |depth| depth as usize);
+        self.registers.sp as usize >= depth.min(STACK_SIZE)
+    }
+
+    /// Decodes the instruction at the given address, if it lies within memory.
+    /// Opcodes that aren't instructions are shown as data words.
+    pub fn disassemble(&self, address: u16) -> Option<String> {
+        let bytes = self
+            .ram
+            .data()
+            .get(address as usize..address as usize + 2)?;
+        let opcode = u16::from_be_bytes([bytes[0], bytes[1]]);
+        match Instruction::decode(opcode) {
+            Some(instruction) => Some(instruction.to_string()),
+            None => Some(format!("DW {:#06x}", opcode)),
+        }
     }
 
     fn execute(&mut self, instruction: Instruction) -> ProgramCounterStatus {
@@ -151,69 +486,91 @@ impl Cpu {
         match instruction {
             Instruction::OpCode00E0 => {
                 self.frame.clear();
+                self.emit(Event::ScreenCleared);
+            }
+
+            Instruction::OpCode00EE if self.registers.sp == 0 => {
+                self.emit(Event::StackUnderflow);
+                self.fault();
+                program_counter_status = ProgramCounterStatus::Repeat;
             }
 
             Instruction::OpCode00EE => {
-                self.registers.pc = self.stack[self.registers.sp as usize];
                 self.registers.sp -= 1;
+                self.registers.pc = self.stack[self.registers.sp as usize];
             }
 
             Instruction::OpCode1NNN(nnn) => {
                 program_counter_status = ProgramCounterStatus::Jump(nnn);
             }
 
+            Instruction::OpCode2NNN(_) if self.is_stack_full() => {
+                self.emit(Event::StackOverflow);
+                self.fault();
+                program_counter_status = ProgramCounterStatus::Repeat;
+            }
+
             Instruction::OpCode2NNN(nnn) => {
-                self.registers.sp += 1;
                 self.stack[self.registers.sp as usize] = self.registers.pc;
+                self.registers.sp += 1;
                 program_counter_status = ProgramCounterStatus::Jump(nnn);
             }
 
             Instruction::OpCode3XNN(x, nn) => {
-                if self.registers.v[x] == nn {
+                if self.registers.v[x as usize] == nn {
                     program_counter_status = ProgramCounterStatus::Skip;
                 }
             }
 
             Instruction::OpCode4XNN(x, nn) => {
-                if self.registers.v[x] != nn {
+                if self.registers.v[x as usize] != nn {
                     program_counter_status = ProgramCounterStatus::Skip;
                 }
             }
 
             Instruction::OpCode5XY0(x, y) => {
-                if self.registers.v[x] == self.registers.v[y] {
+                if self.registers.v[x as usize] == self.registers.v[y as usize] {
                     program_counter_status = ProgramCounterStatus::Skip;
                 }
             }
 
             Instruction::OpCode6XNN(x, nn) => {
-                self.registers.v[x] = nn;
+                self.registers.v[x as usize] = nn;
             }
 
             Instruction::OpCode7XNN(x, nn) => {
-                self.registers.v[x] = self.registers.v[x].wrapping_add(nn);
+                self.registers.v[x as usize] = self.registers.v[x as usize].wrapping_add(nn);
             }
 
             Instruction::OpCode8XY0(x, y) => {
-                self.registers.v[x] = self.registers.v[y];
+                self.registers.v[x as usize] = self.registers.v[y as usize];
             }
 
             Instruction::OpCode8XY1(x, y) => {
-                self.registers.v[x] |= self.registers.v[y];
+                self.registers.v[x as usize] |= self.registers.v[y as usize];
+                if self.quirks.logic {
+                    self.registers.v[0xf] = 0;
+                }
             }
 
             Instruction::OpCode8XY2(x, y) => {
-                self.registers.v[x] &= self.registers.v[y];
+                self.registers.v[x as usize] &= self.registers.v[y as usize];
+                if self.quirks.logic {
+                    self.registers.v[0xf] = 0;
+                }
             }
 
             Instruction::OpCode8XY3(x, y) => {
-                self.registers.v[x] ^= self.registers.v[y];
+                self.registers.v[x as usize] ^= self.registers.v[y as usize];
+                if self.quirks.logic {
+                    self.registers.v[0xf] = 0;
+                }
             }
 
             Instruction::OpCode8XY4(x, y) => {
                 let (result, has_overflown) =
-                    self.registers.v[x].overflowing_add(self.registers.v[y]);
-                self.registers.v[x] = result;
+                    self.registers.v[x as usize].overflowing_add(self.registers.v[y as usize]);
+                self.registers.v[x as usize] = result;
                 if has_overflown {
                     self.registers.v[0xf] = 1;
                 } else {
@@ -223,8 +580,8 @@ impl Cpu {
 
             Instruction::OpCode8XY5(x, y) => {
                 let (result, has_underflown) =
-                    self.registers.v[x].overflowing_sub(self.registers.v[y]);
-                self.registers.v[x] = result;
+                    self.registers.v[x as usize].overflowing_sub(self.registers.v[y as usize]);
+                self.registers.v[x as usize] = result;
                 if !has_underflown {
                     self.registers.v[0xf] = 1;
                 } else {
@@ -233,15 +590,18 @@ impl Cpu {
             }
 
             Instruction::OpCode8XY6(x, y) => {
-                self.registers.v[x] = self.registers.v[y];
-                self.registers.v[0xf] = self.registers.v[x] & 0x1;
-                self.registers.v[x] >>= 1
+                if !self.quirks.shift {
+                    self.registers.v[x as usize] = self.registers.v[y as usize];
+                }
+                let flag = self.registers.v[x as usize] & 0x1;
+                self.registers.v[x as usize] >>= 1;
+                self.registers.v[0xf] = flag;
             }
 
             Instruction::OpCode8XY7(x, y) => {
                 let (result, has_underflown) =
-                    self.registers.v[y].overflowing_sub(self.registers.v[x]);
-                self.registers.v[x] = result;
+                    self.registers.v[y as usize].overflowing_sub(self.registers.v[x as usize]);
+                self.registers.v[x as usize] = result;
                 if !has_underflown {
                     self.registers.v[0xf] = 1;
                 } else {
@@ -250,13 +610,16 @@ impl Cpu {
             }
 
             Instruction::OpCode8XYE(x, y) => {
-                self.registers.v[x] = self.registers.v[y];
-                self.registers.v[0xf] = (self.registers.v[x] >> 7) & 0x1;
-                self.registers.v[x] <<= 1;
+                if !self.quirks.shift {
+                    self.registers.v[x as usize] = self.registers.v[y as usize];
+                }
+                let flag = (self.registers.v[x as usize] >> 7) & 0x1;
+                self.registers.v[x as usize] <<= 1;
+                self.registers.v[0xf] = flag;
             }
 
             Instruction::OpCode9XY0(x, y) => {
-                if self.registers.v[x] != self.registers.v[y] {
+                if self.registers.v[x as usize] != self.registers.v[y as usize] {
                     program_counter_status = ProgramCounterStatus::Skip;
                 }
             }
@@ -266,34 +629,60 @@ impl Cpu {
             }
 
             Instruction::OpCodeBNNN(nnn) => {
+                let x = if self.quirks.jump {
+                    (nnn >> 8) as usize
+                } else {
+                    0
+                };
                 program_counter_status =
-                    ProgramCounterStatus::Jump(nnn + self.registers.v[0] as u16);
+                    ProgramCounterStatus::Jump(nnn + self.registers.v[x] as u16);
             }
 
             Instruction::OpCodeCXNN(x, nn) => {
-                self.registers.v[x] = self.rng.generate::<u8>() & nn;
+                self.registers.v[x as usize] = self.rng.next_byte() & nn;
+            }
+
+            Instruction::OpCodeDXYN(_, _, _) if self.quirks.vblank && !self.vblank_ready => {
+                program_counter_status = ProgramCounterStatus::Repeat;
+            }
+
+            Instruction::OpCodeDXYN(_, _, n) if !self.fits_at_index(n as usize) => {
+                program_counter_status = self.index_out_of_bounds();
             }
 
             Instruction::OpCodeDXYN(x, y, n) => {
-                let start_x = self.registers.v[x] as usize;
-                let start_y = self.registers.v[y] as usize;
+                self.vblank_ready = false;
+                let start_x = self.registers.v[x as usize] as usize;
+                let start_y = self.registers.v[y as usize] as usize;
 
+                self.mark_coverage(self.registers.i, n as usize, Coverage::SPRITE);
                 let sprite = self.ram.read(self.registers.i as usize, n as usize);
 
-                let has_collided = self.frame.draw(sprite, (start_x, start_y));
+                let has_collided = self
+                    .frame
+                    .draw(sprite, (start_x, start_y), self.quirks.clip);
 
                 self.registers.v[0xf] = if has_collided { 1 } else { 0 };
+                self.emit(Event::SpriteDrawn {
+                    x: start_x as u8,
+                    y: start_y as u8,
+                    height: n,
+                });
+                if has_collided {
+                    self.emit(Event::Collision);
+                }
             }
 
+            // only the low nibble of VX selects a key, as on the VIP
             Instruction::OpCodeEX9E(x) => {
-                let key: Key = self.registers.v[x].into();
+                let key: Key = (self.registers.v[x as usize] & 0b1111).into();
                 if self.key_pad.get(key) == KeyState::Pressed {
                     program_counter_status = ProgramCounterStatus::Skip;
                 }
             }
 
             Instruction::OpCodeEXA1(x) => {
-                let key: Key = self.registers.v[x].into();
+                let key: Key = (self.registers.v[x as usize] & 0b1111).into();
                 match self.key_pad.get(key) {
                     KeyState::Pressed => {}
                     _ => program_counter_status = ProgramCounterStatus::Skip,
@@ -301,51 +690,89 @@ impl Cpu {
             }
 
             Instruction::OpCodeFX07(x) => {
-                self.registers.v[x] = self.registers.dt;
+                self.registers.v[x as usize] = self.registers.dt;
             }
 
-            Instruction::OpCodeFX0A(x) => {
-                let key = self.key_pad.find_released_key();
-                match key {
-                    Some(key) => self.registers.v[x] = key.into(),
-                    None => program_counter_status = ProgramCounterStatus::Repeat,
+            Instruction::OpCodeFX0A(x) => match self.key_wait_result() {
+                Some(key) => {
+                    self.registers.v[x as usize] = key.into();
+                    self.waiting_for_key = false;
+                    self.key_wait_key = None;
+                    self.emit(Event::KeyConsumed(key));
                 }
-            }
+                None => {
+                    if !self.waiting_for_key {
+                        self.waiting_for_key = true;
+                        self.emit(Event::WaitingForKey);
+                    }
+                    program_counter_status = ProgramCounterStatus::Repeat;
+                }
+            },
 
             Instruction::OpCodeFX15(x) => {
-                self.registers.dt = self.registers.v[x];
+                self.registers.dt = self.registers.v[x as usize];
             }
 
             Instruction::OpCodeFX18(x) => {
-                self.registers.st = self.registers.v[x];
+                self.registers.st = self.registers.v[x as usize];
             }
 
+            // I may end up past memory, which the instructions reaching memory through it check
             Instruction::OpCodeFX1E(x) => {
-                self.registers.i = self.registers.i.wrapping_add(self.registers.v[x] as u16);
+                self.registers.i = self
+                    .registers
+                    .i
+                    .wrapping_add(self.registers.v[x as usize] as u16);
             }
 
             Instruction::OpCodeFX29(x) => {
-                let nibble = (self.registers.v[x] & 0b1111) as usize;
-                self.registers.i = (FONT_START_OFFSET + (nibble * FONT_CHAR_SIZE)) as u16;
+                let nibble = (self.registers.v[x as usize] & 0b1111) as usize;
+                self.registers.i = self.layout.font_address + (nibble * SMALL_GLYPH_SIZE) as u16;
+            }
+
+            Instruction::OpCodeFX30(x) => {
+                let nibble = (self.registers.v[x as usize] & 0b1111) as usize;
+                // fonts without big glyphs fall back to the small ones
+                let offset = match self.font.big {
+                    Some(_) => SMALL_FONT_SIZE + nibble * BIG_GLYPH_SIZE,
+                    None => nibble * SMALL_GLYPH_SIZE,
+                };
+                self.registers.i = self.layout.font_address + offset as u16;
+            }
+
+            Instruction::OpCodeFX33(_) if !self.fits_at_index(3) => {
+                program_counter_status = self.index_out_of_bounds();
             }
 
             Instruction::OpCodeFX33(x) => {
-                let vx = self.registers.v[x];
+                let vx = self.registers.v[x as usize];
                 let units = vx % 10;
                 let tens = (vx / 10) % 10;
                 let hundreds = (vx / 100) % 10;
-                self.ram
-                    .load(self.registers.i as usize, &[hundreds, tens, units]);
+                self.store(self.registers.i, &[hundreds, tens, units]);
+            }
+
+            Instruction::OpCodeFX55(x) | Instruction::OpCodeFX65(x)
+                if !self.fits_at_index(x as usize + 1) =>
+            {
+                program_counter_status = self.index_out_of_bounds();
             }
 
             Instruction::OpCodeFX55(x) => {
-                let buffer = &self.registers.v[0..=x].to_owned();
-                self.ram.load(self.registers.i as usize, buffer);
+                let buffer = &self.registers.v[0..=x as usize].to_owned();
+                self.store(self.registers.i, buffer);
+                if !self.quirks.load_store {
+                    self.registers.i = self.registers.i.wrapping_add(x as u16 + 1);
+                }
             }
 
             Instruction::OpCodeFX65(x) => {
-                let buffer = self.ram.read(self.registers.i as usize, x + 1);
-                self.registers.v[0..=x].copy_from_slice(buffer);
+                self.mark_coverage(self.registers.i, x as usize + 1, Coverage::READ);
+                let buffer = self.ram.read(self.registers.i as usize, x as usize + 1);
+                self.registers.v[0..=x as usize].copy_from_slice(buffer);
+                if !self.quirks.load_store {
+                    self.registers.i = self.registers.i.wrapping_add(x as u16 + 1);
+                }
             }
         }
         program_counter_status
@@ -0,0 +1,105 @@
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use crate::layout::MEMORY_SIZE;
+
+const LISTING_ROW_SIZE: usize = 16;
+
+/// Tracks how each byte of memory has been used, to tell code apart from graphics and data.
+/// Every byte holds a combination of the flags below, with none set for untouched bytes.
+#[derive(Clone, Debug)]
+pub struct Coverage {
+    map: Vec<u8>,
+}
+
+impl Coverage {
+    /// Fetched as part of an opcode.
+    pub const EXECUTED: u8 = 0b0001;
+    /// Read as sprite data by `DXYN`.
+    pub const SPRITE: u8 = 0b0010;
+    /// Read into registers by `FX65`.
+    pub const READ: u8 = 0b0100;
+    /// Written by `FX33` or `FX55`.
+    pub const WRITTEN: u8 = 0b1000;
+
+    pub fn new() -> Self {
+        Self {
+            map: vec![0; MEMORY_SIZE],
+        }
+    }
+
+    /// Adds a flag to the given number of bytes from an address, ignoring any beyond memory.
+    pub(crate) fn mark(&mut self, address: u16, size: usize, flag: u8) {
+        let start = (address as usize).min(MEMORY_SIZE);
+        let end = (start + size).min(MEMORY_SIZE);
+        for byte in self.map[start..end].iter_mut() {
+            *byte |= flag;
+        }
+    }
+
+    /// Returns the flags of the byte at the given address.
+    pub fn flags(&self, address: u16) -> u8 {
+        self.map.get(address as usize).copied().unwrap_or(0)
+    }
+
+    /// Returns the flags of every byte of memory, one byte per address.
+    pub fn map(&self) -> &[u8] {
+        &self.map
+    }
+
+    /// Formats memory as a hex listing, sixteen bytes to a row, each row followed by a letter per
+    /// byte: `C` for code, `S` for sprite data, `W` for written and `R` for read data, and `.`
+    /// for untouched bytes. Rows that are entirely untouched are left out.
+    pub fn listing(&self, memory: &[u8]) -> String {
+        let mut listing = String::new();
+        let mut skipped = false;
+        for (row, (bytes, flags)) in memory
+            .chunks(LISTING_ROW_SIZE)
+            .zip(self.map.chunks(LISTING_ROW_SIZE))
+            .enumerate()
+        {
+            if flags.iter().all(|&flag| flag == 0) {
+                skipped = true;
+                continue;
+            }
+            if skipped {
+                listing.push_str("...\n");
+                skipped = false;
+            }
+            let _ = write!(listing, "{:#06x} ", row * LISTING_ROW_SIZE);
+            for byte in bytes {
+                let _ = write!(listing, " {:02X}", byte);
+            }
+            listing.push_str("  ");
+            listing.extend(flags.iter().map(|&flag| annotation(flag)));
+            listing.push('\n');
+        }
+        if skipped {
+            listing.push_str("...\n");
+        }
+        listing
+    }
+}
+
+impl Default for Coverage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// bytes used in several ways are shown by their most telling use
+fn annotation(flags: u8) -> char {
+    if flags & Coverage::EXECUTED != 0 {
+        'C'
+    } else if flags & Coverage::SPRITE != 0 {
+        'S'
+    } else if flags & Coverage::WRITTEN != 0 {
+        'W'
+    } else if flags & Coverage::READ != 0 {
+        'R'
+    } else {
+        '.'
+    }
+}
@@ -1,50 +1,55 @@
+use core::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Instruction {
     OpCode00E0,
     OpCode00EE,
     OpCode1NNN(u16),
     OpCode2NNN(u16),
-    OpCode3XNN(usize, u8),
-    OpCode4XNN(usize, u8),
-    OpCode5XY0(usize, usize),
-    OpCode6XNN(usize, u8),
-    OpCode7XNN(usize, u8),
-    OpCode8XY0(usize, usize),
-    OpCode8XY1(usize, usize),
-    OpCode8XY2(usize, usize),
-    OpCode8XY3(usize, usize),
-    OpCode8XY4(usize, usize),
-    OpCode8XY5(usize, usize),
-    OpCode8XY6(usize, usize),
-    OpCode8XY7(usize, usize),
-    OpCode8XYE(usize, usize),
-    OpCode9XY0(usize, usize),
+    OpCode3XNN(u8, u8),
+    OpCode4XNN(u8, u8),
+    OpCode5XY0(u8, u8),
+    OpCode6XNN(u8, u8),
+    OpCode7XNN(u8, u8),
+    OpCode8XY0(u8, u8),
+    OpCode8XY1(u8, u8),
+    OpCode8XY2(u8, u8),
+    OpCode8XY3(u8, u8),
+    OpCode8XY4(u8, u8),
+    OpCode8XY5(u8, u8),
+    OpCode8XY6(u8, u8),
+    OpCode8XY7(u8, u8),
+    OpCode8XYE(u8, u8),
+    OpCode9XY0(u8, u8),
     OpCodeANNN(u16),
     OpCodeBNNN(u16),
-    OpCodeCXNN(usize, u8),
-    OpCodeDXYN(usize, usize, u8),
-    OpCodeEX9E(usize),
-    OpCodeEXA1(usize),
-    OpCodeFX07(usize),
-    OpCodeFX0A(usize),
-    OpCodeFX15(usize),
-    OpCodeFX18(usize),
-    OpCodeFX1E(usize),
-    OpCodeFX29(usize),
-    OpCodeFX33(usize),
-    OpCodeFX55(usize),
-    OpCodeFX65(usize),
+    OpCodeCXNN(u8, u8),
+    OpCodeDXYN(u8, u8, u8),
+    OpCodeEX9E(u8),
+    OpCodeEXA1(u8),
+    OpCodeFX07(u8),
+    OpCodeFX0A(u8),
+    OpCodeFX15(u8),
+    OpCodeFX18(u8),
+    OpCodeFX1E(u8),
+    OpCodeFX29(u8),
+    OpCodeFX30(u8),
+    OpCodeFX33(u8),
+    OpCodeFX55(u8),
+    OpCodeFX65(u8),
 }
 
-impl From<u16> for Instruction {
-    fn from(opcode: u16) -> Self {
+impl Instruction {
+    /// Decodes an opcode, returning `None` if it isn't a known instruction.
+    pub fn decode(opcode: u16) -> Option<Self> {
         let op_type = ((opcode & 0xf000) >> 12) as usize;
-        let x = ((opcode & 0x0f00) >> 8) as usize;
-        let y = ((opcode & 0x00f0) >> 4) as usize;
+        let x = ((opcode & 0x0f00) >> 8) as u8;
+        let y = ((opcode & 0x00f0) >> 4) as u8;
         let nnn = opcode & 0x0fff;
         let nn = (opcode & 0x00ff) as u8;
         let n = (opcode & 0x000f) as u8;
 
-        match (op_type, x, y, n) {
+        let instruction = match (op_type, x, y, n) {
             (0x0, 0x0, 0xe, 0x0) => Instruction::OpCode00E0,
             (0x0, 0x0, 0xe, 0xe) => Instruction::OpCode00EE,
             (0x1, _, _, _) => Instruction::OpCode1NNN(nnn),
@@ -76,10 +81,102 @@ impl From<u16> for Instruction {
             (0xf, _, 0x1, 0x8) => Instruction::OpCodeFX18(x),
             (0xf, _, 0x1, 0xe) => Instruction::OpCodeFX1E(x),
             (0xf, _, 0x2, 0x9) => Instruction::OpCodeFX29(x),
+            (0xf, _, 0x3, 0x0) => Instruction::OpCodeFX30(x),
             (0xf, _, 0x3, 0x3) => Instruction::OpCodeFX33(x),
             (0xf, _, 0x5, 0x5) => Instruction::OpCodeFX55(x),
             (0xf, _, 0x6, 0x5) => Instruction::OpCodeFX65(x),
-            _ => panic!("Invalid opcode: {:x}", opcode),
+            _ => return None,
+        };
+        Some(instruction)
+    }
+
+    /// Returns the pattern of the opcode this instruction was decoded from, such as `DXYN`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Instruction::OpCode00E0 => "00E0",
+            Instruction::OpCode00EE => "00EE",
+            Instruction::OpCode1NNN(..) => "1NNN",
+            Instruction::OpCode2NNN(..) => "2NNN",
+            Instruction::OpCode3XNN(..) => "3XNN",
+            Instruction::OpCode4XNN(..) => "4XNN",
+            Instruction::OpCode5XY0(..) => "5XY0",
+            Instruction::OpCode6XNN(..) => "6XNN",
+            Instruction::OpCode7XNN(..) => "7XNN",
+            Instruction::OpCode8XY0(..) => "8XY0",
+            Instruction::OpCode8XY1(..) => "8XY1",
+            Instruction::OpCode8XY2(..) => "8XY2",
+            Instruction::OpCode8XY3(..) => "8XY3",
+            Instruction::OpCode8XY4(..) => "8XY4",
+            Instruction::OpCode8XY5(..) => "8XY5",
+            Instruction::OpCode8XY6(..) => "8XY6",
+            Instruction::OpCode8XY7(..) => "8XY7",
+            Instruction::OpCode8XYE(..) => "8XYE",
+            Instruction::OpCode9XY0(..) => "9XY0",
+            Instruction::OpCodeANNN(..) => "ANNN",
+            Instruction::OpCodeBNNN(..) => "BNNN",
+            Instruction::OpCodeCXNN(..) => "CXNN",
+            Instruction::OpCodeDXYN(..) => "DXYN",
+            Instruction::OpCodeEX9E(..) => "EX9E",
+            Instruction::OpCodeEXA1(..) => "EXA1",
+            Instruction::OpCodeFX07(..) => "FX07",
+            Instruction::OpCodeFX0A(..) => "FX0A",
+            Instruction::OpCodeFX15(..) => "FX15",
+            Instruction::OpCodeFX18(..) => "FX18",
+            Instruction::OpCodeFX1E(..) => "FX1E",
+            Instruction::OpCodeFX29(..) => "FX29",
+            Instruction::OpCodeFX30(..) => "FX30",
+            Instruction::OpCodeFX33(..) => "FX33",
+            Instruction::OpCodeFX55(..) => "FX55",
+            Instruction::OpCodeFX65(..) => "FX65",
+        }
+    }
+}
+
+impl From<u16> for Instruction {
+    fn from(opcode: u16) -> Self {
+        Instruction::decode(opcode).unwrap_or_else(|| panic!("Invalid opcode: {:x}", opcode))
+    }
+}
+
+/// Disassembles the instruction into the mnemonics of Cowgod's Chip-8 technical reference.
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Instruction::OpCode00E0 => write!(f, "CLS"),
+            Instruction::OpCode00EE => write!(f, "RET"),
+            Instruction::OpCode1NNN(nnn) => write!(f, "JP {:#05x}", nnn),
+            Instruction::OpCode2NNN(nnn) => write!(f, "CALL {:#05x}", nnn),
+            Instruction::OpCode3XNN(x, nn) => write!(f, "SE V{:X}, {:#04x}", x, nn),
+            Instruction::OpCode4XNN(x, nn) => write!(f, "SNE V{:X}, {:#04x}", x, nn),
+            Instruction::OpCode5XY0(x, y) => write!(f, "SE V{:X}, V{:X}", x, y),
+            Instruction::OpCode6XNN(x, nn) => write!(f, "LD V{:X}, {:#04x}", x, nn),
+            Instruction::OpCode7XNN(x, nn) => write!(f, "ADD V{:X}, {:#04x}", x, nn),
+            Instruction::OpCode8XY0(x, y) => write!(f, "LD V{:X}, V{:X}", x, y),
+            Instruction::OpCode8XY1(x, y) => write!(f, "OR V{:X}, V{:X}", x, y),
+            Instruction::OpCode8XY2(x, y) => write!(f, "AND V{:X}, V{:X}", x, y),
+            Instruction::OpCode8XY3(x, y) => write!(f, "XOR V{:X}, V{:X}", x, y),
+            Instruction::OpCode8XY4(x, y) => write!(f, "ADD V{:X}, V{:X}", x, y),
+            Instruction::OpCode8XY5(x, y) => write!(f, "SUB V{:X}, V{:X}", x, y),
+            Instruction::OpCode8XY6(x, y) => write!(f, "SHR V{:X}, V{:X}", x, y),
+            Instruction::OpCode8XY7(x, y) => write!(f, "SUBN V{:X}, V{:X}", x, y),
+            Instruction::OpCode8XYE(x, y) => write!(f, "SHL V{:X}, V{:X}", x, y),
+            Instruction::OpCode9XY0(x, y) => write!(f, "SNE V{:X}, V{:X}", x, y),
+            Instruction::OpCodeANNN(nnn) => write!(f, "LD I, {:#05x}", nnn),
+            Instruction::OpCodeBNNN(nnn) => write!(f, "JP V0, {:#05x}", nnn),
+            Instruction::OpCodeCXNN(x, nn) => write!(f, "RND V{:X}, {:#04x}", x, nn),
+            Instruction::OpCodeDXYN(x, y, n) => write!(f, "DRW V{:X}, V{:X}, {}", x, y, n),
+            Instruction::OpCodeEX9E(x) => write!(f, "SKP V{:X}", x),
+            Instruction::OpCodeEXA1(x) => write!(f, "SKNP V{:X}", x),
+            Instruction::OpCodeFX07(x) => write!(f, "LD V{:X}, DT", x),
+            Instruction::OpCodeFX0A(x) => write!(f, "LD V{:X}, K", x),
+            Instruction::OpCodeFX15(x) => write!(f, "LD DT, V{:X}", x),
+            Instruction::OpCodeFX18(x) => write!(f, "LD ST, V{:X}", x),
+            Instruction::OpCodeFX1E(x) => write!(f, "ADD I, V{:X}", x),
+            Instruction::OpCodeFX29(x) => write!(f, "LD F, V{:X}", x),
+            Instruction::OpCodeFX30(x) => write!(f, "LD HF, V{:X}", x),
+            Instruction::OpCodeFX33(x) => write!(f, "LD B, V{:X}", x),
+            Instruction::OpCodeFX55(x) => write!(f, "LD [I], V{:X}", x),
+            Instruction::OpCodeFX65(x) => write!(f, "LD V{:X}, [I]", x),
         }
     }
 }
@@ -0,0 +1,101 @@
+use alloc::collections::{BTreeSet, VecDeque};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::layout::MEMORY_SIZE;
+
+// the oldest writes are dropped beyond this, programs storing a score every frame add up quickly
+const JOURNAL_CAPACITY: usize = 65_536;
+
+/// A byte of memory written by an instruction.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MemoryWrite {
+    pub address: u16,
+    pub old: u8,
+    pub new: u8,
+    /// The address of the instruction that made the write.
+    pub pc: u16,
+    /// Whether the byte had been executed as part of an opcode before being overwritten.
+    pub self_modifying: bool,
+}
+
+/// Records every byte written to memory by the store instructions, `FX33` and `FX55`,
+/// and which of them patched code that had already run.
+#[derive(Clone, Debug)]
+pub struct WriteJournal {
+    writes: VecDeque<MemoryWrite>,
+    executed: Vec<bool>,
+    self_modified: BTreeSet<u16>,
+}
+
+impl WriteJournal {
+    pub fn new() -> Self {
+        Self {
+            writes: VecDeque::new(),
+            executed: vec![false; MEMORY_SIZE],
+            self_modified: BTreeSet::new(),
+        }
+    }
+
+    /// Notes that the opcode at the given address is being executed.
+    pub(crate) fn mark_executed(&mut self, address: u16, size: usize) {
+        let start = (address as usize).min(MEMORY_SIZE);
+        let end = (start + size).min(MEMORY_SIZE);
+        self.executed[start..end].fill(true);
+    }
+
+    /// Records the bytes about to be written from an address by the instruction at `pc`.
+    pub(crate) fn record(&mut self, pc: u16, address: u16, old: &[u8], new: &[u8]) {
+        for (offset, (&old, &new)) in old.iter().zip(new).enumerate() {
+            let address = address.wrapping_add(offset as u16);
+            let self_modifying = self.executed.get(address as usize) == Some(&true);
+            if self_modifying {
+                self.self_modified.insert(address);
+            }
+            if self.writes.len() == JOURNAL_CAPACITY {
+                self.writes.pop_front();
+            }
+            self.writes.push_back(MemoryWrite {
+                address,
+                old,
+                new,
+                pc,
+                self_modifying,
+            });
+        }
+    }
+
+    /// Forgets which addresses were executed, when the program is restarted or its state replaced.
+    pub(crate) fn clear_executed(&mut self) {
+        self.executed.fill(false);
+    }
+
+    /// Returns the writes recorded, oldest first.
+    /// Only the most recent 65536 are kept.
+    pub fn writes(&self) -> impl Iterator<Item = &MemoryWrite> {
+        self.writes.iter()
+    }
+
+    /// Returns the writes that overwrote code which had already been executed, oldest first.
+    pub fn self_modifications(&self) -> impl Iterator<Item = &MemoryWrite> {
+        self.writes.iter().filter(|write| write.self_modifying)
+    }
+
+    /// Returns every address that has been overwritten after being executed, in order,
+    /// including those whose writes have since been dropped from the journal.
+    pub fn self_modified_addresses(&self) -> Vec<u16> {
+        self.self_modified.iter().copied().collect()
+    }
+
+    /// Discards the writes recorded so far.
+    pub fn clear(&mut self) {
+        self.writes.clear();
+        self.self_modified.clear();
+    }
+}
+
+impl Default for WriteJournal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,13 +1,48 @@
-const MEMORY_SIZE: usize = 4096;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::instructions::Instruction;
+use crate::layout::MEMORY_SIZE;
+
+const OPCODE_SIZE: usize = 2;
 
 pub struct Memory {
     data: [u8; MEMORY_SIZE],
+    // instructions decoded at each address, dropped whenever a byte of their opcode is written.
+    // operands are kept as bytes, so each entry takes 4 bytes
+    decoded: Vec<Option<Instruction>>,
+    caching: bool,
 }
 
 impl Memory {
     pub fn new() -> Self {
         Self {
             data: [0; MEMORY_SIZE],
+            decoded: vec![None; MEMORY_SIZE],
+            caching: true,
+        }
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Gives direct access to memory, dropping every decoded instruction.
+    /// Anything holding on to it must pass a copy of memory to `invalidate_changes` later on.
+    #[cfg(feature = "libretro")]
+    pub fn data_mut(&mut self) -> &mut [u8] {
+        self.decoded.fill(None);
+        &mut self.data
+    }
+
+    /// Drops the decoded instructions over every byte that differs from `snapshot`,
+    /// picking up writes made through `data_mut` since the snapshot was taken.
+    #[cfg(feature = "libretro")]
+    pub fn invalidate_changes(&mut self, snapshot: &[u8]) {
+        for offset in 0..MEMORY_SIZE {
+            if self.data.get(offset) != snapshot.get(offset) {
+                self.invalidate(offset, 1);
+            }
         }
     }
 
@@ -15,8 +50,43 @@ impl Memory {
         &self.data[offset..offset + size]
     }
 
+    /// Returns the instruction at the given address, decoding it on first use unless caching
+    /// is off. Nothing is returned if the opcode there is invalid or runs past the end of memory.
+    pub fn instruction(&mut self, address: usize) -> Option<Instruction> {
+        if let Some(Some(instruction)) = self.decoded.get(address) {
+            return Some(*instruction);
+        }
+        let bytes = self.data.get(address..address + OPCODE_SIZE)?;
+        let instruction = Instruction::decode(u16::from_be_bytes([bytes[0], bytes[1]]))?;
+        if self.caching {
+            self.decoded[address] = Some(instruction);
+        }
+        Some(instruction)
+    }
+
+    pub fn caching(&self) -> bool {
+        self.caching
+    }
+
+    pub fn set_caching(&mut self, caching: bool) {
+        self.caching = caching;
+        self.decoded.fill(None);
+    }
+
     pub fn load(&mut self, offset: usize, bytes: &[u8]) {
         let range = offset..offset + bytes.len();
         self.data[range].copy_from_slice(bytes);
+        self.invalidate(offset, bytes.len());
+    }
+
+    pub fn fill(&mut self, offset: usize, size: usize, value: u8) {
+        self.data[offset..offset + size].fill(value);
+        self.invalidate(offset, size);
+    }
+
+    // an opcode starting the byte before the range overlaps it too
+    fn invalidate(&mut self, offset: usize, size: usize) {
+        let start = offset.saturating_sub(OPCODE_SIZE - 1);
+        self.decoded[start..offset + size].fill(None);
     }
 }
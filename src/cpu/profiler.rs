@@ -0,0 +1,190 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use super::instructions::Instruction;
+use crate::layout::MEMORY_SIZE;
+
+// the most instructions between two reads of the delay timer at the same address
+// for them to count as a loop polling it
+const DT_POLL_WINDOW: u64 = 8;
+
+// calls nested deeper than this are folded into their caller
+const MAX_CALL_DEPTH: usize = 64;
+
+/// Counts where a program spends its time: executions per address and per instruction,
+/// cycles spent waiting for a key in `FX0A` and polling the delay timer, and time per call stack.
+#[derive(Clone, Debug)]
+pub struct Profiler {
+    cycles: u64,
+    hits: Vec<u64>,
+    instructions: BTreeMap<&'static str, u64>,
+    key_wait_cycles: u64,
+    dt_wait_cycles: u64,
+    last_dt_read: Option<(u16, u64)>,
+    calls: Vec<u16>,
+    stacks: BTreeMap<Vec<u16>, u64>,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self {
+            cycles: 0,
+            hits: vec![0; MEMORY_SIZE],
+            instructions: BTreeMap::new(),
+            key_wait_cycles: 0,
+            dt_wait_cycles: 0,
+            last_dt_read: None,
+            calls: Vec::new(),
+            stacks: BTreeMap::new(),
+        }
+    }
+
+    /// Records an instruction about to be executed at the given address.
+    pub(crate) fn record(&mut self, pc: u16, instruction: &Instruction, dt: u8, key_wait: bool) {
+        self.cycles += 1;
+        if let Some(hits) = self.hits.get_mut(pc as usize) {
+            *hits += 1;
+        }
+        *self.instructions.entry(instruction.name()).or_insert(0) += 1;
+        match self.stacks.get_mut(&self.calls) {
+            Some(count) => *count += 1,
+            None => {
+                self.stacks.insert(self.calls.clone(), 1);
+            }
+        }
+
+        match *instruction {
+            Instruction::OpCode2NNN(nnn) if self.calls.len() < MAX_CALL_DEPTH => {
+                self.calls.push(nnn);
+            }
+            Instruction::OpCode00EE => {
+                self.calls.pop();
+            }
+            Instruction::OpCodeFX0A(_) if key_wait => self.key_wait_cycles += 1,
+            // reading a running delay timer again shortly after, at the same address,
+            // means the program is spinning until it runs out
+            Instruction::OpCodeFX07(_) if dt > 0 => {
+                if let Some((address, cycle)) = self.last_dt_read {
+                    let elapsed = self.cycles - cycle;
+                    if address == pc && elapsed <= DT_POLL_WINDOW {
+                        self.dt_wait_cycles += elapsed;
+                    }
+                }
+                self.last_dt_read = Some((pc, self.cycles));
+            }
+            _ => (),
+        }
+    }
+
+    /// Forgets the call stack, when the program is restarted or its state replaced.
+    pub(crate) fn clear_calls(&mut self) {
+        self.calls.clear();
+        self.last_dt_read = None;
+    }
+
+    /// Returns the number of instructions recorded.
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    /// Returns how many times the instruction at the given address was executed.
+    pub fn hits(&self, address: u16) -> u64 {
+        self.hits.get(address as usize).copied().unwrap_or(0)
+    }
+
+    /// Returns the addresses that were executed with their counts, most executed first.
+    pub fn hot_addresses(&self) -> Vec<(u16, u64)> {
+        let mut addresses: Vec<(u16, u64)> = self
+            .hits
+            .iter()
+            .enumerate()
+            .filter(|(_, &hits)| hits > 0)
+            .map(|(address, &hits)| (address as u16, hits))
+            .collect();
+        addresses.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        addresses
+    }
+
+    /// Returns how many times each instruction was executed, keyed by opcode pattern such as
+    /// `DXYN`, most executed first.
+    pub fn instruction_counts(&self) -> Vec<(&'static str, u64)> {
+        let mut counts: Vec<(&'static str, u64)> = self
+            .instructions
+            .iter()
+            .map(|(&name, &count)| (name, count))
+            .collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        counts
+    }
+
+    /// Returns the cycles spent in `FX0A` waiting for a key.
+    pub fn key_wait_cycles(&self) -> u64 {
+        self.key_wait_cycles
+    }
+
+    /// Returns the cycles spent in loops polling the delay timer with `FX07` until it runs out.
+    pub fn dt_wait_cycles(&self) -> u64 {
+        self.dt_wait_cycles
+    }
+
+    /// Formats a plain text report of the totals, the instruction histogram
+    /// and the given number of most executed addresses.
+    pub fn report(&self, top_addresses: usize) -> String {
+        let mut report = String::new();
+        let percent = |count: u64| count as f64 * 100.0 / self.cycles.max(1) as f64;
+        let _ = writeln!(report, "cycles: {}", self.cycles);
+        let _ = writeln!(
+            report,
+            "waiting for key (FX0A): {} ({:.1}%)",
+            self.key_wait_cycles,
+            percent(self.key_wait_cycles)
+        );
+        let _ = writeln!(
+            report,
+            "polling delay timer: {} ({:.1}%)",
+            self.dt_wait_cycles,
+            percent(self.dt_wait_cycles)
+        );
+
+        let _ = writeln!(report, "\ninstructions:");
+        for (name, count) in self.instruction_counts() {
+            let _ = writeln!(report, "  {}  {:>10}  {:5.1}%", name, count, percent(count));
+        }
+
+        let _ = writeln!(report, "\naddresses:");
+        for (address, hits) in self.hot_addresses().into_iter().take(top_addresses) {
+            let _ = writeln!(
+                report,
+                "  {:#06x}  {:>10}  {:5.1}%",
+                address,
+                hits,
+                percent(hits)
+            );
+        }
+        report
+    }
+
+    /// Formats the time spent in each call stack as folded stacks, one `main;0x0240;0x0262 1234`
+    /// line per stack, for flame graph tools such as `inferno` or `flamegraph.pl`.
+    /// Frames are the addresses of the subroutines called.
+    pub fn folded_stacks(&self) -> String {
+        let mut folded = String::new();
+        for (calls, count) in self.stacks.iter() {
+            folded.push_str("main");
+            for address in calls {
+                let _ = write!(folded, ";{:#06x}", address);
+            }
+            let _ = writeln!(folded, " {}", count);
+        }
+        folded
+    }
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
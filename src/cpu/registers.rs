@@ -5,7 +5,8 @@ use {
     tsify::Tsify,
 };
 
-const PROGRAM_START_OFFSET: u16 = 0x200;
+use crate::layout::DEFAULT_PROGRAM_START;
+
 const V_REG_COUNT: usize = 16;
 
 #[cfg_attr(feature = "wasm", derive(Tsify, Serialize, Deserialize))]
@@ -23,7 +24,7 @@ pub struct Registers {
 impl Registers {
     pub fn new() -> Self {
         Self {
-            pc: PROGRAM_START_OFFSET,
+            pc: DEFAULT_PROGRAM_START,
             i: 0,
             sp: 0,
             dt: 0,
@@ -32,3 +33,9 @@ impl Registers {
         }
     }
 }
+
+impl Default for Registers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
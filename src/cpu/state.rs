@@ -0,0 +1,330 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+use super::{Cpu, ONE_SECOND_IN_MICRO_SECONDS, STACK_SIZE, TIMER_STEP_THRESHOLD_MICRO_SECONDS};
+use crate::frame::{FRAME_HEIGHT, FRAME_WIDTH};
+use crate::keypad::{Key, KeyState, KEY_COUNT};
+use crate::layout::{MemoryFill, MemoryLayout, MEMORY_SIZE};
+use crate::platform::Platform;
+use crate::quirks::Quirks;
+use crate::speed::check_speed;
+
+const MAGIC: &[u8; 4] = b"C8ST";
+const VERSION: u8 = 4;
+
+/// Errors that can occur when loading a saved state.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StateError {
+    InvalidHeader,
+    UnsupportedVersion(u8),
+    UnexpectedEnd,
+    InvalidState,
+}
+
+impl Cpu {
+    /// Encodes everything needed to resume execution later: the speed, platform, quirks,
+    /// memory layout and stack depth, the registers, stack, memory, display, timers, keypad,
+    /// the key `FX0A` waits on and the random state.
+    ///
+    /// The format is `C8ST` and a version byte, followed by each part in turn with numbers in
+    /// little endian and the display packed to one bit per pixel. The stack takes two bytes per
+    /// address on it, so the size of the state varies with it.
+    pub fn save_state(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(MEMORY_SIZE + 512);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+
+        bytes.extend_from_slice(&self.instructions_per_second.to_le_bytes());
+        bytes.push(encode_platform(self.platform));
+        bytes.push(encode_quirks(self.quirks));
+        bytes.extend_from_slice(&self.layout.font_address.to_le_bytes());
+        bytes.extend_from_slice(&self.layout.program_start.to_le_bytes());
+        bytes.extend_from_slice(&encode_fill(self.layout.fill));
+        bytes.extend_from_slice(&encode_stack_depth(self.stack_depth));
+
+        let registers = &self.registers;
+        bytes.extend_from_slice(&registers.pc.to_le_bytes());
+        bytes.extend_from_slice(&registers.i.to_le_bytes());
+        bytes.extend_from_slice(&[registers.sp, registers.dt, registers.st]);
+        bytes.extend_from_slice(&registers.v);
+        // only the addresses in use, which the stack pointer counts
+        for address in self.stack() {
+            bytes.extend_from_slice(&address.to_le_bytes());
+        }
+        bytes.extend_from_slice(self.ram.data());
+
+        for y in 0..FRAME_HEIGHT {
+            for x in (0..FRAME_WIDTH).step_by(u8::BITS as usize) {
+                let byte = (0..u8::BITS as usize).fold(0u8, |byte, bit| {
+                    (byte << 1) | self.frame.is_pixel_on(x + bit, y) as u8
+                });
+                bytes.push(byte);
+            }
+        }
+
+        for accumulator in [
+            self.cpu_time_accumulator,
+            self.dt_time_accumulator,
+            self.st_time_accumulator,
+            self.frame_time_accumulator,
+        ] {
+            bytes.extend_from_slice(&accumulator.to_le_bytes());
+        }
+        bytes.push(self.vblank_ready as u8);
+        for key in 0..KEY_COUNT as u8 {
+            bytes.push(self.key_pad.get(key.into()).into());
+        }
+        bytes.push(self.waiting_for_key as u8);
+        bytes.extend_from_slice(&encode_key_wait_key(self.key_wait_key));
+        bytes.extend_from_slice(&self.rng.state().to_le_bytes());
+        bytes
+    }
+
+    /// Restores a state produced by `save_state`.
+    /// Nothing is changed if the state is invalid.
+    pub fn load_state(&mut self, bytes: &[u8]) -> Result<(), StateError> {
+        let mut reader = Reader { bytes };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(StateError::InvalidHeader);
+        }
+        let version = reader.u8()?;
+        if version != VERSION {
+            return Err(StateError::UnsupportedVersion(version));
+        }
+
+        let instructions_per_second = reader.u32()?;
+        check_speed(instructions_per_second).map_err(|_| StateError::InvalidState)?;
+        let platform = decode_platform(reader.u8()?)?;
+        let quirks = decode_quirks(reader.u8()?);
+        let layout = MemoryLayout {
+            font_address: reader.u16()?,
+            program_start: reader.u16()?,
+            fill: decode_fill(reader.u8()?, reader.u8()?)?,
+        };
+        let stack_depth = decode_stack_depth(reader.u8()?, reader.u8()?)?;
+        layout
+            .check(self.font.size())
+            .map_err(|_| StateError::InvalidState)?;
+
+        let mut registers = self.registers.clone();
+        registers.pc = reader.u16()?;
+        registers.i = reader.u16()?;
+        registers.sp = reader.u8()?;
+        registers.dt = reader.u8()?;
+        registers.st = reader.u8()?;
+        let v = reader.take(registers.v.len())?;
+        registers.v.copy_from_slice(v);
+        let mut stack = [0; STACK_SIZE];
+        for address in stack[..registers.sp as usize].iter_mut() {
+            *address = reader.u16()?;
+        }
+        let ram = reader.take(MEMORY_SIZE)?;
+        let pixels = reader.take(FRAME_WIDTH * FRAME_HEIGHT / u8::BITS as usize)?;
+        let accumulators = [reader.u32()?, reader.u32()?, reader.u32()?, reader.u32()?];
+        // each accumulator holds less than the time of the step it counts towards
+        let [cpu_time, timer_times @ ..] = accumulators;
+        if cpu_time >= ONE_SECOND_IN_MICRO_SECONDS / instructions_per_second
+            || timer_times
+                .iter()
+                .any(|&time| time >= TIMER_STEP_THRESHOLD_MICRO_SECONDS)
+        {
+            return Err(StateError::InvalidState);
+        }
+        let vblank_ready = reader.u8()? != 0;
+        let mut keys = [KeyState::None; KEY_COUNT];
+        for key in keys.iter_mut() {
+            *key = KeyState::try_from(reader.u8()?).map_err(|_| StateError::InvalidState)?;
+        }
+        let waiting_for_key = decode_bool(reader.u8()?)?;
+        let key_wait_key = decode_key_wait_key(reader.u8()?, reader.u8()?)?;
+        let random_state = reader.u64()?;
+        if !reader.bytes.is_empty() {
+            return Err(StateError::InvalidState);
+        }
+
+        self.set_speed(instructions_per_second)
+            .map_err(|_| StateError::InvalidState)?;
+        self.platform = platform;
+        self.quirks = quirks;
+        self.layout = layout;
+        self.stack_depth = stack_depth;
+        self.registers = registers;
+        self.stack = stack;
+        self.ram.load(0, ram);
+        for (index, byte) in pixels.iter().enumerate() {
+            let y = index * u8::BITS as usize / FRAME_WIDTH;
+            let x = index * u8::BITS as usize % FRAME_WIDTH;
+            for bit in 0..u8::BITS as usize {
+                let on = (byte >> (u8::BITS as usize - 1 - bit)) & 0x1 == 1;
+                self.frame.set_pixel(x + bit, y, on);
+            }
+        }
+        [
+            self.cpu_time_accumulator,
+            self.dt_time_accumulator,
+            self.st_time_accumulator,
+            self.frame_time_accumulator,
+        ] = accumulators;
+        self.vblank_ready = vblank_ready;
+        self.waiting_for_key = waiting_for_key;
+        self.key_wait_key = key_wait_key;
+        self.faulted = false;
+        for (key, state) in keys.iter().enumerate() {
+            self.key_pad.set((key as u8).into(), *state);
+        }
+        self.rng.set_state(random_state);
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.clear_calls();
+        }
+        if let Some(journal) = self.journal.as_mut() {
+            journal.clear_executed();
+        }
+        Ok(())
+    }
+}
+
+fn encode_platform(platform: Platform) -> u8 {
+    match platform {
+        Platform::Vip => 0,
+        Platform::Modern => 1,
+        Platform::Schip => 2,
+    }
+}
+
+fn decode_platform(value: u8) -> Result<Platform, StateError> {
+    match value {
+        0 => Ok(Platform::Vip),
+        1 => Ok(Platform::Modern),
+        2 => Ok(Platform::Schip),
+        _ => Err(StateError::InvalidState),
+    }
+}
+
+// one bit per quirk, in the order they are declared
+fn encode_quirks(quirks: Quirks) -> u8 {
+    [
+        quirks.shift,
+        quirks.load_store,
+        quirks.clip,
+        quirks.vblank,
+        quirks.jump,
+        quirks.logic,
+    ]
+    .iter()
+    .enumerate()
+    .fold(0, |byte, (bit, &quirk)| byte | (quirk as u8) << bit)
+}
+
+fn decode_quirks(byte: u8) -> Quirks {
+    let quirk = |bit: u8| byte & (1 << bit) != 0;
+    Quirks {
+        shift: quirk(0),
+        load_store: quirk(1),
+        clip: quirk(2),
+        vblank: quirk(3),
+        jump: quirk(4),
+        logic: quirk(5),
+    }
+}
+
+fn encode_fill(fill: MemoryFill) -> [u8; 2] {
+    match fill {
+        MemoryFill::Zero => [0, 0],
+        MemoryFill::Random => [1, 0],
+        MemoryFill::Pattern(byte) => [2, byte],
+    }
+}
+
+fn decode_fill(kind: u8, byte: u8) -> Result<MemoryFill, StateError> {
+    match kind {
+        0 => Ok(MemoryFill::Zero),
+        1 => Ok(MemoryFill::Random),
+        2 => Ok(MemoryFill::Pattern(byte)),
+        _ => Err(StateError::InvalidState),
+    }
+}
+
+fn encode_stack_depth(stack_depth: Option<u8>) -> [u8; 2] {
+    match stack_depth {
+        None => [0, 0],
+        Some(depth) => [1, depth],
+    }
+}
+
+fn decode_stack_depth(kind: u8, depth: u8) -> Result<Option<u8>, StateError> {
+    match kind {
+        0 => Ok(None),
+        1 => Ok(Some(depth)),
+        _ => Err(StateError::InvalidState),
+    }
+}
+
+fn encode_key_wait_key(key: Option<Key>) -> [u8; 2] {
+    match key {
+        None => [0, 0],
+        Some(key) => [1, key.into()],
+    }
+}
+
+fn decode_key_wait_key(kind: u8, key: u8) -> Result<Option<Key>, StateError> {
+    match (kind, key) {
+        (0, _) => Ok(None),
+        (1, 0x0..=0xf) => Ok(Some(key.into())),
+        _ => Err(StateError::InvalidState),
+    }
+}
+
+fn decode_bool(value: u8) -> Result<bool, StateError> {
+    match value {
+        0 => Ok(false),
+        1 => Ok(true),
+        _ => Err(StateError::InvalidState),
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, size: usize) -> Result<&'a [u8], StateError> {
+        if self.bytes.len() < size {
+            return Err(StateError::UnexpectedEnd);
+        }
+        let (taken, rest) = self.bytes.split_at(size);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, StateError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, StateError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, StateError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, StateError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateError::InvalidHeader => write!(f, "invalid state header"),
+            StateError::UnsupportedVersion(version) => {
+                write!(f, "unsupported state version: {}", version)
+            }
+            StateError::UnexpectedEnd => write!(f, "unexpected end of state data"),
+            StateError::InvalidState => write!(f, "invalid state"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for StateError {}
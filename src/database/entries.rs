@@ -0,0 +1,15 @@
+// This file is generated by `scripts/generate_database.py` from the chip-8-database
+// (https://github.com/chip-8/chip-8-database), do not edit it by hand.
+// Entries must stay ordered by hash, since lookups use a binary search.
+
+#[allow(unused_imports)]
+use super::Entry;
+#[allow(unused_imports)]
+use crate::keypad::Key;
+#[allow(unused_imports)]
+use crate::platform::Platform;
+#[allow(unused_imports)]
+use crate::quirks::Quirks;
+
+#[rustfmt::skip]
+pub static ENTRIES: &[Entry] = &[];
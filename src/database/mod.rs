@@ -0,0 +1,44 @@
+use crate::frame::Color;
+use crate::keypad::Key;
+use crate::platform::Platform;
+use crate::quirks::Quirks;
+
+mod entries;
+
+/// Recommended settings for a known program, taken from the community chip-8-database.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Entry {
+    /// SHA-1 hash of the program.
+    pub sha1: [u8; 20],
+    pub title: &'static str,
+    pub author: Option<&'static str>,
+    pub platform: Platform,
+    pub quirks: Quirks,
+    /// Instructions per frame, at 60 frames per second.
+    pub tick_rate: Option<u32>,
+    /// Colours of pixels that are on and off.
+    pub colors: Option<(Color, Color)>,
+    /// What the program uses keys for, e.g. `("left", Key::Key4)`.
+    pub key_hints: &'static [(&'static str, Key)],
+}
+
+impl Entry {
+    /// Returns the recommended speed in instructions per second, if the entry has a tick rate.
+    pub fn speed(&self) -> Option<u32> {
+        self.tick_rate
+            .and_then(|tick_rate| tick_rate.checked_mul(60))
+    }
+}
+
+/// Looks up the recommended settings for a program by its SHA-1 hash.
+pub fn lookup(sha1: &[u8; 20]) -> Option<&'static Entry> {
+    entries::ENTRIES
+        .binary_search_by(|entry| entry.sha1.cmp(sha1))
+        .ok()
+        .map(|index| &entries::ENTRIES[index])
+}
+
+/// Returns every entry in the database, ordered by hash.
+pub fn entries() -> &'static [Entry] {
+    entries::ENTRIES
+}
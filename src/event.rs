@@ -0,0 +1,47 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+
+#[cfg(feature = "wasm")]
+use crate::alloc::string::ToString;
+
+use crate::keypad::Key;
+
+/// Something that happened while the virtual machine ran, so that hosts can react to it,
+/// such as starting audio or redrawing, without comparing state every frame.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Event {
+    /// The sound timer was set, so the buzzer should start.
+    SoundStarted,
+    /// The sound timer ran out or was cleared, so the buzzer should stop.
+    SoundStopped,
+    /// `00E0` cleared the display.
+    ScreenCleared,
+    /// `DXYN` drew a sprite `height` rows tall at the given position.
+    SpriteDrawn { x: u8, y: u8, height: u8 },
+    /// The sprite just drawn turned off a pixel that was on.
+    Collision,
+    /// `FX0A` started waiting for a key to be pressed and released.
+    WaitingForKey,
+    /// `FX0A` took the given key.
+    KeyConsumed(Key),
+    /// A subroutine was called with the stack already full.
+    StackOverflow,
+    /// `00EE` returned with no subroutine to return from.
+    StackUnderflow,
+    /// An instruction reached past the end of memory from the given address in I.
+    MemoryOutOfBounds { address: u16 },
+    /// The program can't continue, because the stack overflowed or underflowed,
+    /// memory was reached past its end, or an invalid opcode was reached.
+    /// It stays faulted until the virtual machine is reset.
+    Fault,
+    /// The program finished by jumping to itself with the timers run out, and `update` stopped.
+    Finished,
+    /// The program stopped making progress, see `Chip8::set_hang_detection`, and `update` stopped.
+    Hung,
+}
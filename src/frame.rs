@@ -4,8 +4,15 @@ pub const FRAME_WIDTH: usize = 64;
 /// The height of the frame in pixels.
 pub const FRAME_HEIGHT: usize = 32;
 
-const PIXEL_ON: [u8; 4] = [u8::MAX, u8::MAX, u8::MAX, u8::MAX];
-const PIXEL_OFF: [u8; 4] = [u8::MIN, u8::MIN, u8::MIN, u8::MAX];
+/// A colour as RGBA bytes.
+pub type Color = [u8; 4];
+
+/// The default colour of pixels that are on.
+pub const DEFAULT_ON_COLOR: Color = [u8::MAX, u8::MAX, u8::MAX, u8::MAX];
+
+/// The default colour of pixels that are off.
+pub const DEFAULT_OFF_COLOR: Color = [u8::MIN, u8::MIN, u8::MIN, u8::MAX];
+
 const FRAME_SIZE: usize = FRAME_WIDTH * FRAME_HEIGHT;
 const BYTES_PER_PIXEL: usize = 4;
 const BYTES_PER_ROW: usize = FRAME_WIDTH * BYTES_PER_PIXEL;
@@ -14,23 +21,66 @@ const BUFFER_SIZE: usize = FRAME_SIZE * BYTES_PER_PIXEL;
 #[derive(Clone)]
 pub struct FrameBuffer {
     pub buffer: [u8; BUFFER_SIZE],
+    on_color: Color,
+    off_color: Color,
 }
 
 impl FrameBuffer {
     pub fn new() -> Self {
-        Self {
+        let mut frame = Self {
             buffer: [0; BUFFER_SIZE],
+            on_color: DEFAULT_ON_COLOR,
+            off_color: DEFAULT_OFF_COLOR,
+        };
+        frame.clear();
+        frame
+    }
+
+    pub fn colors(&self) -> (Color, Color) {
+        (self.on_color, self.off_color)
+    }
+
+    /// Sets the colours of pixels that are on and off, repainting the pixels already drawn.
+    /// The colours must differ, since the buffer is used to tell which pixels are on, so equal
+    /// colours are ignored. Returns whether the colours were set.
+    pub fn set_colors(&mut self, on_color: Color, off_color: Color) -> bool {
+        if on_color == off_color {
+            return false;
+        }
+        for pixel in self.buffer.chunks_exact_mut(BYTES_PER_PIXEL) {
+            if pixel == self.on_color {
+                pixel.copy_from_slice(&on_color);
+            } else {
+                pixel.copy_from_slice(&off_color);
+            }
         }
+        self.on_color = on_color;
+        self.off_color = off_color;
+        true
+    }
+
+    pub fn is_pixel_on(&self, x: usize, y: usize) -> bool {
+        let pixel_offset = (x * BYTES_PER_PIXEL) + y * BYTES_PER_ROW;
+        self.buffer[pixel_offset..pixel_offset + BYTES_PER_PIXEL] == self.on_color
+    }
+
+    pub fn set_pixel(&mut self, x: usize, y: usize, on: bool) {
+        let pixel_offset = (x * BYTES_PER_PIXEL) + y * BYTES_PER_ROW;
+        let color = if on { self.on_color } else { self.off_color };
+        self.buffer[pixel_offset..pixel_offset + BYTES_PER_PIXEL].copy_from_slice(&color);
     }
 
     pub fn clear(&mut self) {
+        let off_color = self.off_color;
         self.buffer
             .iter_mut()
             .enumerate()
-            .for_each(|(index, pixel)| *pixel = PIXEL_OFF[index % BYTES_PER_PIXEL])
+            .for_each(|(index, pixel)| *pixel = off_color[index % BYTES_PER_PIXEL])
     }
 
-    pub fn draw(&mut self, sprite: &[u8], coordinates: (usize, usize)) -> bool {
+    /// Draws the sprite, returning whether any pixel was turned off.
+    /// Parts of the sprite that fall off the screen are clipped, or wrap around if `clip` is false.
+    pub fn draw(&mut self, sprite: &[u8], coordinates: (usize, usize), clip: bool) -> bool {
         // wrap the starting coordinates
         let start_x = coordinates.0 % FRAME_WIDTH;
         let start_y = coordinates.1 % FRAME_HEIGHT;
@@ -40,11 +90,15 @@ impl FrameBuffer {
         for (i, byte) in sprite.iter().enumerate() {
             // iterate over each bit
             for j in 0..u8::BITS as usize {
-                let x = start_x + j;
-                let y = start_y + i;
-                // stop drawing if we go off the screen
+                let mut x = start_x + j;
+                let mut y = start_y + i;
+                // stop drawing if we go off the screen, or wrap around to the other side
                 if x >= FRAME_WIDTH || y >= FRAME_HEIGHT {
-                    continue;
+                    if clip {
+                        continue;
+                    }
+                    x %= FRAME_WIDTH;
+                    y %= FRAME_HEIGHT;
                 }
                 // check the state of the bit
                 let bit = (byte >> (u8::BITS as usize - 1 - j)) & 0x1;
@@ -55,11 +109,11 @@ impl FrameBuffer {
                         .get_mut(pixel_offset..pixel_offset + BYTES_PER_PIXEL)
                         .expect("pixel out of bounds");
 
-                    if pixel == PIXEL_ON {
-                        pixel.copy_from_slice(&PIXEL_OFF);
+                    if pixel == self.on_color {
+                        pixel.copy_from_slice(&self.off_color);
                         has_collided = true;
                     } else {
-                        pixel.copy_from_slice(&PIXEL_ON);
+                        pixel.copy_from_slice(&self.on_color);
                     }
                 }
             }
@@ -67,3 +121,9 @@ impl FrameBuffer {
         has_collided
     }
 }
+
+impl Default for FrameBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,67 @@
+use crate::cpu::Cpu;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Detects a program that has stopped making progress: once a frame, everything but the
+/// program counter is compared with the frame before, and the program is hung after the
+/// given number of frames without a change.
+pub(crate) struct HangDetector {
+    frames: u32,
+    last_frame: u32,
+    digest: u64,
+    unchanged_frames: u32,
+}
+
+impl HangDetector {
+    pub(crate) fn new(frames: u32) -> Self {
+        Self {
+            frames,
+            last_frame: 0,
+            digest: 0,
+            unchanged_frames: 0,
+        }
+    }
+
+    /// Returns the number of unchanged frames after which the program is hung.
+    pub(crate) fn frames(&self) -> u32 {
+        self.frames
+    }
+
+    /// Compares the state with the one a frame before, if a frame has passed since the last
+    /// check, and returns whether it hasn't changed for long enough.
+    pub(crate) fn is_hung(&mut self, cpu: &Cpu) -> bool {
+        if cpu.frames() == self.last_frame {
+            return false;
+        }
+        self.last_frame = cpu.frames();
+        let digest = digest(cpu);
+        if digest == self.digest {
+            self.unchanged_frames = self.unchanged_frames.saturating_add(1);
+        } else {
+            self.digest = digest;
+            self.unchanged_frames = 0;
+        }
+        self.unchanged_frames >= self.frames
+    }
+}
+
+// the program counter is left out, it moves around the loop a hung program is stuck in
+fn digest(cpu: &Cpu) -> u64 {
+    let registers = &cpu.registers;
+    let mut hash = FNV_OFFSET_BASIS;
+    let mut feed = |bytes: &[u8]| {
+        for &byte in bytes {
+            hash = (hash ^ byte as u64).wrapping_mul(FNV_PRIME);
+        }
+    };
+    feed(&registers.i.to_le_bytes());
+    feed(&[registers.sp, registers.dt, registers.st]);
+    feed(&registers.v);
+    for address in cpu.stack() {
+        feed(&address.to_le_bytes());
+    }
+    feed(cpu.ram.data());
+    feed(&cpu.frame.buffer);
+    hash
+}
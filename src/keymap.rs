@@ -0,0 +1,304 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::keypad::Key;
+
+const QWERTY: [(&str, Key); 16] = [
+    ("1", Key::Key1),
+    ("2", Key::Key2),
+    ("3", Key::Key3),
+    ("4", Key::KeyC),
+    ("q", Key::Key4),
+    ("w", Key::Key5),
+    ("e", Key::Key6),
+    ("r", Key::KeyD),
+    ("a", Key::Key7),
+    ("s", Key::Key8),
+    ("d", Key::Key9),
+    ("f", Key::KeyE),
+    ("z", Key::KeyA),
+    ("x", Key::Key0),
+    ("c", Key::KeyB),
+    ("v", Key::KeyF),
+];
+
+const AZERTY: [(&str, Key); 16] = [
+    ("1", Key::Key1),
+    ("2", Key::Key2),
+    ("3", Key::Key3),
+    ("4", Key::KeyC),
+    ("a", Key::Key4),
+    ("z", Key::Key5),
+    ("e", Key::Key6),
+    ("r", Key::KeyD),
+    ("q", Key::Key7),
+    ("s", Key::Key8),
+    ("d", Key::Key9),
+    ("f", Key::KeyE),
+    ("w", Key::KeyA),
+    ("x", Key::Key0),
+    ("c", Key::KeyB),
+    ("v", Key::KeyF),
+];
+
+const NUMPAD: [(&str, Key); 16] = [
+    ("numpad0", Key::Key0),
+    ("numpad1", Key::Key1),
+    ("numpad2", Key::Key2),
+    ("numpad3", Key::Key3),
+    ("numpad4", Key::Key4),
+    ("numpad5", Key::Key5),
+    ("numpad6", Key::Key6),
+    ("numpad7", Key::Key7),
+    ("numpad8", Key::Key8),
+    ("numpad9", Key::Key9),
+    ("numpaddivide", Key::KeyA),
+    ("numpadmultiply", Key::KeyB),
+    ("numpadsubtract", Key::KeyC),
+    ("numpadadd", Key::KeyD),
+    ("numpadenter", Key::KeyE),
+    ("numpaddecimal", Key::KeyF),
+];
+
+const VIP: [(&str, Key); 16] = [
+    ("0", Key::Key0),
+    ("1", Key::Key1),
+    ("2", Key::Key2),
+    ("3", Key::Key3),
+    ("4", Key::Key4),
+    ("5", Key::Key5),
+    ("6", Key::Key6),
+    ("7", Key::Key7),
+    ("8", Key::Key8),
+    ("9", Key::Key9),
+    ("a", Key::KeyA),
+    ("b", Key::KeyB),
+    ("c", Key::KeyC),
+    ("d", Key::KeyD),
+    ("e", Key::KeyE),
+    ("f", Key::KeyF),
+];
+
+/// Maps host keys onto the Chip-8 keypad.
+///
+/// Host keys are identified by the label the host reports for them, such as
+/// `KeyboardEvent.key` in the browser. Numpad keys use their `KeyboardEvent.code` name
+/// (e.g. `Numpad7`), since their labels clash with the number row.
+/// Identifiers are matched case-insensitively, ignoring surrounding whitespace.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "Bindings"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyMap {
+    bindings: BTreeMap<String, Key>,
+}
+
+// the serialized form of a key map, whose host keys are normalized as it's turned into one
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct Bindings {
+    bindings: BTreeMap<String, Key>,
+}
+
+#[cfg(feature = "serde")]
+impl From<Bindings> for KeyMap {
+    fn from(bindings: Bindings) -> Self {
+        let mut key_map = KeyMap::empty();
+        for (host_key, key) in bindings.bindings {
+            key_map.set(&host_key, key);
+        }
+        key_map
+    }
+}
+
+/// Error returned when a key map cannot be parsed from a string.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseKeyMapError {
+    binding: String,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl KeyMap {
+    /// Creates a key map with no bindings.
+    pub fn empty() -> Self {
+        Self {
+            bindings: BTreeMap::new(),
+        }
+    }
+
+    /// The common layout, using the left hand block `1234 / QWER / ASDF / ZXCV` of a QWERTY keyboard.
+    pub fn qwerty() -> Self {
+        Self::from_table(&QWERTY)
+    }
+
+    /// The common layout for AZERTY keyboards, using the block `1234 / AZER / QSDF / WXCV`.
+    pub fn azerty() -> Self {
+        Self::from_table(&AZERTY)
+    }
+
+    /// Maps the numpad digits to the keys of the same value and the operator keys to `A` - `F`.
+    pub fn numpad() -> Self {
+        Self::from_table(&NUMPAD)
+    }
+
+    /// Maps every host key labelled with a hex digit to the key with the same label,
+    /// as if typing on the physical keypad of the COSMAC VIP.
+    pub fn vip() -> Self {
+        Self::from_table(&VIP)
+    }
+
+    /// Returns the key bound to the given host key, if any.
+    pub fn get(&self, host_key: &str) -> Option<Key> {
+        self.bindings.get(&normalize(host_key)).copied()
+    }
+
+    /// Binds a host key to a key, replacing any previous binding for that host key.
+    /// This is how per-program overrides are applied on top of a layout.
+    pub fn set(&mut self, host_key: &str, key: Key) {
+        self.bindings.insert(normalize(host_key), key);
+    }
+
+    /// Removes the binding for the given host key.
+    pub fn remove(&mut self, host_key: &str) {
+        self.bindings.remove(&normalize(host_key));
+    }
+
+    /// Applies every binding of `overrides` on top of this key map.
+    pub fn apply_overrides(&mut self, overrides: &KeyMap) {
+        for (host_key, key) in overrides.bindings.iter() {
+            self.bindings.insert(host_key.clone(), *key);
+        }
+    }
+}
+
+impl KeyMap {
+    fn from_table(table: &[(&str, Key)]) -> Self {
+        Self {
+            bindings: table
+                .iter()
+                .map(|(host_key, key)| (host_key.to_string(), *key))
+                .collect(),
+        }
+    }
+
+    /// Returns an iterator over the bindings, ordered by host key.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Key)> {
+        self.bindings
+            .iter()
+            .map(|(host_key, key)| (host_key.as_str(), *key))
+    }
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        Self::qwerty()
+    }
+}
+
+/// The string form is a comma separated list of `host_key=hex_digit` bindings, e.g. `q=4,w=5`.
+/// A `\`, `,` or whitespace character in a host key is escaped with a backslash,
+/// so the space bar is written as `\ =5` and the comma key as `\,=5`.
+impl fmt::Display for KeyMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, (host_key, key)) in self.iter().enumerate() {
+            if index > 0 {
+                write!(f, ",")?;
+            }
+            for c in host_key.chars() {
+                if c == '\\' || c == ',' || c.is_whitespace() {
+                    write!(f, "\\")?;
+                }
+                write!(f, "{}", c)?;
+            }
+            write!(f, "={:X}", u8::from(key))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for KeyMap {
+    type Err = ParseKeyMapError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut key_map = KeyMap::empty();
+        for binding in split_bindings(s).iter().map(|b| b.trim()) {
+            if binding.is_empty() {
+                continue;
+            }
+            let error = || ParseKeyMapError {
+                binding: binding.to_string(),
+            };
+            // split on the last '=' so that '=' itself can be bound
+            let (host_key, value) = binding.rsplit_once('=').ok_or_else(error)?;
+            let value = u8::from_str_radix(value.trim(), 16).map_err(|_| error())?;
+            let host_key = unescape(host_key).ok_or_else(error)?;
+            if host_key.is_empty() || value > 0xF {
+                return Err(error());
+            }
+            key_map.set(&host_key, value.into());
+        }
+        Ok(key_map)
+    }
+}
+
+// splits on the commas that aren't escaped, leaving the escapes in place
+fn split_bindings(s: &str) -> Vec<&str> {
+    let mut bindings = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (index, c) in s.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            ',' => {
+                bindings.push(&s[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    bindings.push(&s[start..]);
+    bindings
+}
+
+// removes the backslash escapes, failing on a trailing unpaired backslash
+fn unescape(host_key: &str) -> Option<String> {
+    let mut unescaped = String::new();
+    let mut chars = host_key.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            unescaped.push(chars.next()?);
+        } else {
+            unescaped.push(c);
+        }
+    }
+    Some(unescaped)
+}
+
+impl fmt::Display for ParseKeyMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid key binding: {}", self.binding)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseKeyMapError {}
+
+// whitespace is only kept when it is the whole key, so that the space bar can be bound
+fn normalize(host_key: &str) -> String {
+    let trimmed = host_key.trim();
+    if trimmed.is_empty() {
+        host_key.to_string()
+    } else {
+        trimmed.to_ascii_lowercase()
+    }
+}
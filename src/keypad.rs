@@ -1,7 +1,14 @@
+use alloc::collections::VecDeque;
+
 #[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 pub const KEY_COUNT: usize = 16;
+// the oldest key events are dropped beyond this, for hosts that queue keys while paused
+const KEY_QUEUE_CAPACITY: usize = 256;
 
 pub struct KeyPad {
     state: [KeyState; KEY_COUNT],
@@ -9,7 +16,8 @@ pub struct KeyPad {
 
 /// Enum representing the state of a key on the keypad.
 #[cfg_attr(feature = "wasm", wasm_bindgen)]
-#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum KeyState {
     Released,
     Pressed,
@@ -18,7 +26,8 @@ pub enum KeyState {
 
 /// Enum representing a key on the keypad.
 #[cfg_attr(feature = "wasm", wasm_bindgen)]
-#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Key {
     Key0,
     Key1,
@@ -53,15 +62,6 @@ impl KeyPad {
         self.state[u8::from(key) as usize] = state;
     }
 
-    pub fn find_released_key(&self) -> Option<Key> {
-        for (i, key) in self.state.iter().enumerate() {
-            if *key == KeyState::Released {
-                return Some((i as u8).into());
-            }
-        }
-        None
-    }
-
     pub fn reset_released_keys(&mut self) {
         for key in self.state.iter_mut() {
             if *key == KeyState::Released {
@@ -71,6 +71,65 @@ impl KeyPad {
     }
 }
 
+/// Key events waiting to be applied, each at a time in microseconds from the start of the
+/// current update. Only the latest events are kept, which leave the keys as the host last set them.
+pub(crate) struct KeyQueue {
+    events: VecDeque<(Key, KeyState, u32)>,
+    // keys changed before the current instruction
+    changed: u16,
+}
+
+impl KeyQueue {
+    pub fn new() -> Self {
+        Self {
+            events: VecDeque::new(),
+            changed: 0,
+        }
+    }
+
+    pub fn push(&mut self, key: Key, state: KeyState, offset: u32) {
+        if self.events.len() == KEY_QUEUE_CAPACITY {
+            self.events.pop_front();
+        }
+        self.events.push_back((key, state, offset));
+    }
+
+    /// Returns the time of the next event, if any.
+    pub fn next_offset(&self) -> Option<u32> {
+        self.events.front().map(|&(_, _, offset)| offset)
+    }
+
+    /// Takes the next event due by the given time. A key only changes once per instruction,
+    /// so that a press and release arriving together are both seen, and events stay in order
+    /// by holding back everything after one that has to wait.
+    pub fn pop_due(&mut self, time: u32) -> Option<(Key, KeyState)> {
+        let &(key, state, offset) = self.events.front()?;
+        let bit = 1 << u8::from(key);
+        if offset > time || self.changed & bit != 0 {
+            return None;
+        }
+        self.changed |= bit;
+        self.events.pop_front();
+        Some((key, state))
+    }
+
+    /// Marks the start of the next instruction.
+    pub fn next_instruction(&mut self) {
+        self.changed = 0;
+    }
+
+    /// Makes the times of the events left relative to the start of the next update.
+    pub fn rebase(&mut self, elapsed: u32) {
+        for (_, _, offset) in self.events.iter_mut() {
+            *offset = offset.saturating_sub(elapsed);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+}
+
 impl From<u8> for Key {
     fn from(key: u8) -> Self {
         match key {
@@ -117,3 +176,26 @@ impl From<Key> for u8 {
         }
     }
 }
+
+impl From<KeyState> for u8 {
+    fn from(state: KeyState) -> Self {
+        match state {
+            KeyState::Released => 0,
+            KeyState::Pressed => 1,
+            KeyState::None => 2,
+        }
+    }
+}
+
+impl TryFrom<u8> for KeyState {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(KeyState::Released),
+            1 => Ok(KeyState::Pressed),
+            2 => Ok(KeyState::None),
+            _ => Err(value),
+        }
+    }
+}
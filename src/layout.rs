@@ -0,0 +1,134 @@
+use core::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+
+#[cfg(feature = "wasm")]
+use crate::alloc::string::ToString;
+
+/// The size of memory in bytes.
+pub const MEMORY_SIZE: usize = 4096;
+
+/// The address programs are loaded at by default.
+pub const DEFAULT_PROGRAM_START: u16 = 0x200;
+
+/// The address the font is loaded at by default.
+pub const DEFAULT_FONT_ADDRESS: u16 = 0;
+
+/// Where the font and program live in memory, and what the rest of memory starts out as.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MemoryLayout {
+    /// The address of the font, which `FX29` points into.
+    pub font_address: u16,
+    /// The address programs are loaded at, and execution starts from.
+    pub program_start: u16,
+    /// What memory is filled with before the font and program are loaded.
+    pub fill: MemoryFill,
+}
+
+/// Enum representing what memory is filled with on reset.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum MemoryFill {
+    #[default]
+    Zero,
+    /// Bytes drawn from the random number generator.
+    Random,
+    /// The given byte repeated.
+    Pattern(u8),
+}
+
+/// Errors that can occur when placing data in memory.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MemoryError {
+    OutOfBounds {
+        address: usize,
+        size: usize,
+    },
+    /// The font would lie in the memory programs are loaded into.
+    OverlapsProgram {
+        address: usize,
+        size: usize,
+    },
+}
+
+impl MemoryLayout {
+    /// The layout of the ETI-660, whose programs start at 0x600.
+    pub const fn eti660() -> Self {
+        Self {
+            program_start: 0x600,
+            ..Self::new()
+        }
+    }
+
+    const fn new() -> Self {
+        Self {
+            font_address: DEFAULT_FONT_ADDRESS,
+            program_start: DEFAULT_PROGRAM_START,
+            fill: MemoryFill::Zero,
+        }
+    }
+
+    /// Returns the largest program, in bytes, that fits between the program start and the end of memory.
+    pub fn max_program_size(&self) -> usize {
+        MEMORY_SIZE.saturating_sub(self.program_start as usize)
+    }
+
+    /// Checks that a font of `font_size` bytes fits into memory at the font address below the
+    /// program start, which programs are loaded from up to the end of memory, and that the
+    /// program start lies in memory.
+    pub(crate) fn check(&self, font_size: usize) -> Result<(), MemoryError> {
+        let font_address = self.font_address as usize;
+        let program_start = self.program_start as usize;
+        check_bounds(font_address, font_size)?;
+        check_bounds(program_start, 1)?;
+        if font_address + font_size > program_start {
+            return Err(MemoryError::OverlapsProgram {
+                address: font_address,
+                size: font_size,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Default for MemoryLayout {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Checks that `size` bytes at `address` fit into memory.
+pub fn check_bounds(address: usize, size: usize) -> Result<(), MemoryError> {
+    if address + size > MEMORY_SIZE {
+        return Err(MemoryError::OutOfBounds { address, size });
+    }
+    Ok(())
+}
+
+impl fmt::Display for MemoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MemoryError::OutOfBounds { address, size } => write!(
+                f,
+                "{} bytes at {:#05x} do not fit into {} bytes of memory",
+                size, address, MEMORY_SIZE
+            ),
+            MemoryError::OverlapsProgram { address, size } => write!(
+                f,
+                "{} bytes of font at {:#05x} overlap the memory programs are loaded into",
+                size, address
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MemoryError {}
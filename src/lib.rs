@@ -8,14 +8,62 @@ extern crate std;
 #[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
+use alloc::boxed::Box;
+use alloc::collections::BTreeSet;
+use alloc::string::String;
+use alloc::vec::Vec;
+pub use cpu::coverage::Coverage;
+pub use cpu::journal::{MemoryWrite, WriteJournal};
+pub use cpu::profiler::Profiler;
 pub use cpu::registers::Registers;
-pub use frame::{FrameBuffer, FRAME_HEIGHT, FRAME_WIDTH};
-pub use keypad::{Key, KeyState};
+pub use cpu::state::StateError;
 use cpu::Cpu;
+use database::Entry;
+pub use event::Event;
+pub use font::{Font, FontStyle, BIG_FONT_SIZE, BIG_GLYPH_SIZE, SMALL_FONT_SIZE, SMALL_GLYPH_SIZE};
+pub use frame::{
+    Color, FrameBuffer, DEFAULT_OFF_COLOR, DEFAULT_ON_COLOR, FRAME_HEIGHT, FRAME_WIDTH,
+};
+use hang::HangDetector;
+pub use keymap::{KeyMap, ParseKeyMapError};
+use keypad::KeyQueue;
+pub use keypad::{Key, KeyState};
+pub use layout::{
+    MemoryError, MemoryFill, MemoryLayout, DEFAULT_FONT_ADDRESS, DEFAULT_PROGRAM_START, MEMORY_SIZE,
+};
+pub use movie::{Checkpoint, InputEvent, Movie, MovieError};
+use movie::{Playback, Recorder};
+pub use platform::Platform;
+pub use quirks::Quirks;
+pub use random::{RandomSource, SequenceSource, WyRandSource};
+pub use rom::{Rom, RomError};
+pub use run_state::RunState;
+pub use speed::{SpeedError, MAX_SPEED, MIN_SPEED};
 
+#[cfg(feature = "capi")]
+pub mod capi;
 mod cpu;
+pub mod database;
+mod event;
+mod font;
 mod frame;
+mod hang;
+mod keymap;
 mod keypad;
+mod layout;
+#[cfg(feature = "libretro")]
+pub mod libretro;
+mod movie;
+#[cfg(feature = "octo")]
+pub mod octo;
+mod platform;
+#[cfg(feature = "python")]
+mod python;
+mod quirks;
+mod random;
+mod rom;
+mod run_state;
+mod speed;
 
 #[cfg(feature = "wasm")]
 mod wasm;
@@ -24,6 +72,21 @@ mod wasm;
 #[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub struct Chip8 {
     cpu: Cpu,
+    key_map: KeyMap,
+    recorder: Option<Recorder>,
+    playback: Option<Playback>,
+    idle_loop_skip: bool,
+    fast_forward: u32,
+    run_state: RunState,
+    breakpoints: BTreeSet<u16>,
+    // set on resuming, the time passed to the next update may span the pause
+    discard_time_delta: bool,
+    // set on resuming from a breakpoint, so that it isn't hit again straight away
+    step_over_breakpoint: bool,
+    key_queue: KeyQueue,
+    // the emulated time in microseconds since the start of the current update
+    key_clock: u32,
+    hang_detector: Option<HangDetector>,
 }
 
 #[cfg_attr(feature = "wasm", wasm_bindgen)]
@@ -34,6 +97,18 @@ impl Chip8 {
     pub fn new(seed: u32) -> Self {
         Self {
             cpu: Cpu::new(seed),
+            key_map: KeyMap::default(),
+            recorder: None,
+            playback: None,
+            idle_loop_skip: false,
+            fast_forward: 1,
+            run_state: RunState::Running,
+            breakpoints: BTreeSet::new(),
+            discard_time_delta: false,
+            step_over_breakpoint: false,
+            key_queue: KeyQueue::new(),
+            key_clock: 0,
+            hang_detector: None,
         }
     }
 
@@ -47,31 +122,387 @@ impl Chip8 {
         FRAME_HEIGHT as u32
     }
 
+    /// Reseeds the random number generator.
+    pub fn reseed(&mut self, seed: u32) {
+        self.cpu.rng.reseed(seed.into());
+        self.checkpoint();
+    }
+
+    /// Returns the current state of the random number generator.
+    pub fn random_state(&self) -> u64 {
+        self.cpu.rng.state()
+    }
+
+    /// Restores a state of the random number generator previously returned by `random_state`.
+    pub fn set_random_state(&mut self, state: u64) {
+        self.cpu.rng.set_state(state);
+        self.checkpoint();
+    }
+
     /// Returns the speed of the virtual machine in instructions per second.
     pub fn speed(&self) -> u32 {
         self.cpu.instructions_per_second
     }
 
     /// Sets the speed of the virtual machine in instructions per second.
+    /// Speeds outside `MIN_SPEED..=MAX_SPEED` are clamped into it, use `try_set_speed` to
+    /// have them rejected instead.
     pub fn set_speed(&mut self, instructions_per_second: u32) {
-        self.cpu.set_speed(instructions_per_second);
+        let _ = self.try_set_speed(instructions_per_second.clamp(MIN_SPEED, MAX_SPEED));
+    }
+
+    /// Sets the speed of the virtual machine in instructions per second.
+    /// Fails if the speed is outside `MIN_SPEED..=MAX_SPEED`.
+    pub fn try_set_speed(&mut self, instructions_per_second: u32) -> Result<(), SpeedError> {
+        self.cpu.set_speed(instructions_per_second)?;
+        self.checkpoint();
+        Ok(())
+    }
+
+    /// Returns the platform whose limits the virtual machine follows.
+    pub fn platform(&self) -> Platform {
+        self.cpu.platform
+    }
+
+    /// Sets the platform whose limits the virtual machine follows,
+    /// along with the stack depth it allows.
+    pub fn set_platform(&mut self, platform: Platform) {
+        self.cpu.platform = platform;
+        self.cpu.stack_depth = platform.stack_depth();
+        self.checkpoint();
+    }
+
+    /// Returns how many subroutine calls can be nested, or `None` when only the 8-bit
+    /// stack pointer limits them, to 255.
+    pub fn stack_depth(&self) -> Option<u8> {
+        self.cpu.stack_depth
+    }
+
+    /// Sets how many subroutine calls can be nested. Calling a subroutine beyond it
+    /// reports a stack overflow and faults the program. Setting the platform replaces it.
+    pub fn set_stack_depth(&mut self, stack_depth: Option<u8>) {
+        self.cpu.stack_depth = stack_depth;
+        self.checkpoint();
+    }
+
+    /// Returns the quirks the virtual machine follows.
+    pub fn quirks(&self) -> Quirks {
+        self.cpu.quirks
+    }
+
+    /// Sets the quirks the virtual machine follows.
+    pub fn set_quirks(&mut self, quirks: Quirks) {
+        self.cpu.quirks = quirks;
+        self.checkpoint();
+    }
+
+    /// Returns where the font and program are placed in memory.
+    pub fn memory_layout(&self) -> MemoryLayout {
+        self.cpu.layout
+    }
+
+    /// Sets where the font and program are placed in memory, and resets the virtual machine.
+    /// Fails if the font or program start lie outside of memory, or the font overlaps the memory
+    /// programs are loaded into, from the program start to the end of memory.
+    pub fn set_memory_layout(&mut self, layout: MemoryLayout) -> Result<(), MemoryError> {
+        layout.check(self.cpu.font.size())?;
+        self.cpu.layout = layout;
+        self.cpu.reset();
+        self.checkpoint();
+        Ok(())
+    }
+
+    /// Installs one of the fonts interpreters shipped with, see `set_font`.
+    pub fn set_font_style(&mut self, style: FontStyle) -> Result<(), MemoryError> {
+        self.set_font(Font::from(style))
+    }
+
+    /// Looks the program up in the database and applies its recommended settings.
+    /// Returns whether the program was found.
+    pub fn apply_recommended_settings(&mut self, rom: &Rom) -> bool {
+        match database::lookup(&rom.sha1()) {
+            Some(entry) => {
+                self.apply_entry(entry);
+                true
+            }
+            None => false,
+        }
     }
 
     /// Loads a program into the virtual machine.
+    /// This panics if the program doesn't fit into memory, use `load_rom` to have it validated.
     pub fn load(&mut self, bytes: &[u8]) {
         self.cpu.load_program(bytes);
+        self.checkpoint();
+    }
+
+    /// Loads bytes into memory at the given address, leaving the rest of memory untouched.
+    /// Fails if the bytes don't fit into memory.
+    pub fn load_at(&mut self, address: u16, bytes: &[u8]) -> Result<(), MemoryError> {
+        layout::check_bounds(address as usize, bytes.len())?;
+        self.cpu.ram.load(address as usize, bytes);
+        self.checkpoint();
+        Ok(())
+    }
+
+    /// Loads a validated program into the virtual machine, after clearing any previous program.
+    /// Fails if the program is too large for the active platform or memory layout.
+    pub fn load_rom(&mut self, rom: &Rom) -> Result<(), RomError> {
+        self.check_rom_size(rom, self.cpu.platform)?;
+        self.cpu.clear_program();
+        self.cpu.load_program(rom.bytes());
+        self.checkpoint();
+        Ok(())
     }
 
     /// This will progress the virtual machine by the given time delta.
     /// It takes into account any accumulated time from previous calls that were less than a full cycle.
     /// The time delta given is in microseconds.
+    /// Nothing happens unless the virtual machine is running.
     pub fn update(&mut self, time_delta: u32) {
-        self.cpu.update(time_delta);
+        if self.run_state() != RunState::Running {
+            return;
+        }
+        self.run(time_delta);
+        self.key_queue.rebase(time_delta);
+        self.key_clock = 0;
+    }
+
+    fn run(&mut self, time_delta: u32) {
+        let time_delta = if self.discard_time_delta {
+            self.discard_time_delta = false;
+            0
+        } else {
+            time_delta.saturating_mul(self.fast_forward)
+        };
+        // the host may have changed the speed before this update when recording
+        self.apply_due_checkpoint();
+        let mut due = self.cpu.instructions_due(time_delta);
+        while due > 0 {
+            let step_over_breakpoint = core::mem::take(&mut self.step_over_breakpoint);
+            if !step_over_breakpoint && self.breakpoints.contains(&self.cpu.registers.pc) {
+                self.run_state = RunState::Breakpoint;
+                return;
+            }
+            if let Some(event) = self.stop_event() {
+                self.cpu.emit(event);
+                self.run_state = RunState::Halted;
+                return;
+            }
+            // skipping gives the same results, but is kept simple by leaving playback
+            // and breakpoints alone
+            if self.idle_loop_skip && self.playback.is_none() && self.breakpoints.is_empty() {
+                let skipped = self
+                    .cpu
+                    .skip_idle_loop(due.min(self.instructions_until_key_event()));
+                if skipped > 0 {
+                    if let Some(recorder) = self.recorder.as_mut() {
+                        recorder.skip(skipped);
+                    }
+                    let skipped_time = skipped * self.cpu.micro_seconds_per_instruction();
+                    self.key_clock = self.key_clock.saturating_add(skipped_time);
+                    due -= skipped;
+                    continue;
+                }
+            }
+            self.cycle();
+            due -= 1;
+        }
+    }
+
+    // the event telling why the program can't make progress anymore, if it can't
+    fn stop_event(&mut self) -> Option<Event> {
+        if self.cpu.is_finished() {
+            return Some(Event::Finished);
+        }
+        self.hang_detector
+            .as_mut()
+            .is_some_and(|detector| detector.is_hung(&self.cpu))
+            .then_some(Event::Hung)
+    }
+
+    fn instructions_until_key_event(&self) -> u32 {
+        match self.key_queue.next_offset() {
+            Some(offset) => {
+                let due_at = offset.saturating_mul(self.fast_forward);
+                due_at.saturating_sub(self.key_clock) / self.cpu.micro_seconds_per_instruction()
+            }
+            None => u32::MAX,
+        }
+    }
+
+    /// Returns how many times faster than the time given to `update` the virtual machine runs.
+    pub fn fast_forward(&self) -> u32 {
+        self.fast_forward
     }
 
-    /// Executes a single cycle of the virtual machine.
+    /// Runs the virtual machine the given number of times faster than the time given to `update`,
+    /// with the timers sped up alike. A multiplier of 1, or 0, runs it at normal speed.
+    pub fn set_fast_forward(&mut self, multiplier: u32) {
+        self.fast_forward = multiplier.max(1);
+    }
+
+    /// Returns the number of frames without progress after which the program is hung,
+    /// if hangs are detected.
+    pub fn hang_detection(&self) -> Option<u32> {
+        self.hang_detector.as_ref().map(HangDetector::frames)
+    }
+
+    /// Sets whether `update` stops, with the run state halted, once nothing but the program
+    /// counter has changed for the given number of frames, for tools running programs unattended.
+    /// Programs waiting on input count as hung, as do ones only polling a key.
+    /// Jumps to the same instruction, which end a program, are detected regardless.
+    pub fn set_hang_detection(&mut self, frames: Option<u32>) {
+        self.hang_detector = frames.map(HangDetector::new);
+    }
+
+    /// Returns whether `update` skips loops polling the delay timer.
+    pub fn idle_loop_skip(&self) -> bool {
+        self.idle_loop_skip
+    }
+
+    /// Sets whether `update` skips ahead through tight loops polling the delay timer, such as
+    /// `FX07` `3X00` `1NNN`, ticking the timers until the value the loop sees would change.
+    /// The results are the same as running every instruction.
+    pub fn set_idle_loop_skip(&mut self, enabled: bool) {
+        self.idle_loop_skip = enabled;
+    }
+
+    /// Executes a single cycle of the virtual machine, whatever its run state.
+    /// Stepping from a breakpoint leaves the virtual machine paused.
     pub fn step(&mut self) {
+        if self.run_state == RunState::Breakpoint {
+            self.run_state = RunState::Paused;
+        }
+        self.step_over_breakpoint = false;
+        self.cycle();
+    }
+
+    /// Returns whether the virtual machine is running, paused or stopped.
+    pub fn run_state(&self) -> RunState {
+        if self.cpu.is_faulted() {
+            RunState::Error
+        } else {
+            self.run_state
+        }
+    }
+
+    /// Pauses the virtual machine, so that `update` does nothing until it is resumed.
+    pub fn pause(&mut self) {
+        if matches!(self.run_state, RunState::Running | RunState::Breakpoint) {
+            self.run_state = RunState::Paused;
+        }
+    }
+
+    /// Resumes the virtual machine after a pause or a breakpoint.
+    /// The time delta given to the next `update` is discarded, since it may span the pause.
+    pub fn resume(&mut self) {
+        if matches!(self.run_state, RunState::Paused | RunState::Breakpoint) {
+            self.step_over_breakpoint = self.run_state == RunState::Breakpoint;
+            self.run_state = RunState::Running;
+            self.discard_time_delta = true;
+        }
+    }
+
+    /// Stops `update` before the instruction at the given address is executed.
+    pub fn add_breakpoint(&mut self, address: u16) {
+        self.breakpoints.insert(address);
+    }
+
+    /// Removes the breakpoint at the given address.
+    pub fn remove_breakpoint(&mut self, address: u16) {
+        self.breakpoints.remove(&address);
+    }
+
+    /// Removes every breakpoint.
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    /// Returns the addresses of the breakpoints, in order.
+    pub fn breakpoints(&self) -> Vec<u16> {
+        self.breakpoints.iter().copied().collect()
+    }
+
+    // restores what the host changed at this point of the movie being played back
+    fn apply_due_checkpoint(&mut self) {
+        if let Some(state) = self
+            .playback
+            .as_mut()
+            .and_then(|playback| playback.due_checkpoint())
+        {
+            // every checkpoint was checked when playback started
+            let _ = self.cpu.load_state(state);
+        }
+    }
+
+    // a cycle as run by `update`, feeding in the key events due or any movie being played back
+    fn cycle(&mut self) {
+        self.apply_due_checkpoint();
+        if let Some(playback) = self.playback.as_mut() {
+            while let Some(event) = playback.next_due_event() {
+                self.cpu.apply_key_event(event.key, event.state);
+            }
+            playback.step();
+            if playback.is_finished() {
+                self.playback = None;
+            }
+        } else {
+            let time = self.key_clock / self.fast_forward;
+            while let Some((key, state)) = self.key_queue.pop_due(time) {
+                if let Some(recorder) = self.recorder.as_mut() {
+                    recorder.record(key, state);
+                }
+                self.cpu.apply_key_event(key, state);
+            }
+        }
+        self.key_queue.next_instruction();
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.step();
+        }
         self.cpu.step();
+        let instruction_time = self.cpu.micro_seconds_per_instruction();
+        self.key_clock = self.key_clock.saturating_add(instruction_time);
+    }
+
+    /// Returns whether the program has faulted, on a stack overflow or underflow, memory reached
+    /// past its end or an invalid opcode. Nothing more is executed until the virtual machine is reset.
+    pub fn is_faulted(&self) -> bool {
+        self.cpu.is_faulted()
+    }
+
+    /// Returns whether the program is stopped at `FX0A`, waiting for a key to be pressed and released.
+    pub fn is_waiting_for_key(&self) -> bool {
+        self.cpu.is_waiting_for_key()
+    }
+
+    /// Returns whether instructions are skipped while waiting for a key.
+    pub fn key_wait_fast_path(&self) -> bool {
+        self.cpu.key_wait_fast_path
+    }
+
+    /// Sets whether to stop executing instructions while `FX0A` waits for a key, ticking only the
+    /// timers until a key is released. This gives the same results with less work, but a profile
+    /// will only count the first cycle of each wait.
+    pub fn set_key_wait_fast_path(&mut self, enabled: bool) {
+        self.cpu.key_wait_fast_path = enabled;
+    }
+
+    /// Returns whether decoded instructions are cached.
+    pub fn instruction_cache(&self) -> bool {
+        self.cpu.ram.caching()
+    }
+
+    /// Sets whether to keep each instruction once decoded until memory under it is written,
+    /// rather than decoding every opcode as it runs. It is on by default and gives the same
+    /// results either way, so turning it off is only useful to measure what it saves.
+    pub fn set_instruction_cache(&mut self, enabled: bool) {
+        self.cpu.ram.set_caching(enabled);
+    }
+
+    /// Takes the oldest event that hasn't been taken yet.
+    pub fn next_event(&mut self) -> Option<Event> {
+        self.cpu.events.pop_front()
     }
 
     /// Returns a copy of the frame buffer.
@@ -87,19 +518,329 @@ impl Chip8 {
     /// Sets the registers.
     pub fn set_registers(&mut self, registers: Registers) {
         self.cpu.registers = registers;
+        self.checkpoint();
     }
 
-    /// Passes a key event to the virtual machine.
+    /// Returns a copy of memory.
+    pub fn memory(&self) -> Vec<u8> {
+        self.cpu.ram.data().to_vec()
+    }
+
+    /// Returns the return addresses on the call stack, oldest first.
+    pub fn stack(&self) -> Vec<u16> {
+        self.cpu.stack().to_vec()
+    }
+
+    /// Returns the disassembly of the instruction about to be executed.
+    pub fn current_instruction(&self) -> Option<String> {
+        self.cpu.disassemble(self.cpu.registers.pc)
+    }
+
+    /// Returns the disassembly of the instruction at the given address,
+    /// or nothing if it lies outside of memory.
+    pub fn disassemble(&self, address: u16) -> Option<String> {
+        self.cpu.disassemble(address)
+    }
+
+    /// Saves the state of the virtual machine, so that it can be resumed with `load_state`.
+    /// Recordings, playback, the key map, font and colours are not included.
+    pub fn save_state(&self) -> Vec<u8> {
+        self.cpu.save_state()
+    }
+
+    /// Restores a state saved with `save_state`.
+    /// The virtual machine is left untouched if the state is invalid.
+    /// A halted program runs again, since the state restored may be from before it finished.
+    pub fn load_state(&mut self, bytes: &[u8]) -> Result<(), StateError> {
+        self.cpu.load_state(bytes)?;
+        self.restart();
+        self.checkpoint();
+        Ok(())
+    }
+
+    /// Passes a key event to the virtual machine, to be applied before the next instruction.
+    /// Key events are ignored while a movie is being played back.
     pub fn handle_key_event(&mut self, key: Key, state: KeyState) {
-        self.cpu.key_pad.set(key, state);
+        self.queue_key_event(key, state, 0);
+    }
+
+    /// Queues a key event to be applied at the instruction emulated the given number of
+    /// microseconds into the next `update`, so that input keeps its timing within a frame.
+    /// Events are applied in the order they are queued, and a key changes at most once per
+    /// instruction, so a press and release queued together are both seen by the program.
+    /// Events queued while the virtual machine isn't running wait for it to run again, with
+    /// the oldest dropped once 256 are waiting.
+    /// Key events are ignored while a movie is being played back.
+    pub fn queue_key_event(&mut self, key: Key, state: KeyState, time_offset: u32) {
+        if self.playback.is_some() {
+            return;
+        }
+        self.key_queue.push(key, state, time_offset);
+    }
+
+    /// Returns a copy of the key map used by `handle_host_key_event`.
+    pub fn key_map(&self) -> KeyMap {
+        self.key_map.clone()
+    }
+
+    /// Sets the key map used by `handle_host_key_event`.
+    /// The QWERTY layout is used by default.
+    pub fn set_key_map(&mut self, key_map: KeyMap) {
+        self.key_map = key_map;
+    }
+
+    /// Passes a host key event to the virtual machine, translating it through the key map.
+    /// Returns whether the host key is bound to a key.
+    pub fn handle_host_key_event(&mut self, host_key: &str, state: KeyState) -> bool {
+        match self.key_map.get(host_key) {
+            Some(key) => {
+                self.handle_key_event(key, state);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Starts recording key events into a movie from the current state, discarding any recording
+    /// in progress. Changes made to the virtual machine while recording, such as its speed or quirks,
+    /// are recorded as well.
+    pub fn start_recording(&mut self) {
+        self.recorder = Some(Recorder::new(self.cpu.save_state()));
+    }
+
+    /// Stops recording and returns the recorded movie, if a recording was in progress.
+    pub fn stop_recording(&mut self) -> Option<Movie> {
+        self.recorder.take().map(Recorder::finish)
+    }
+
+    /// Returns whether a movie is being recorded.
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    /// Starts profiling execution, discarding any profile in progress.
+    pub fn start_profiling(&mut self) {
+        self.cpu.profiler = Some(Profiler::new());
+    }
+
+    /// Returns whether execution is being profiled.
+    pub fn is_profiling(&self) -> bool {
+        self.cpu.profiler.is_some()
+    }
+
+    /// Returns a plain text report of the profile in progress, listing the given number of most
+    /// executed addresses.
+    pub fn profile_report(&self, top_addresses: usize) -> Option<String> {
+        self.cpu
+            .profiler
+            .as_ref()
+            .map(|profiler| profiler.report(top_addresses))
+    }
+
+    /// Returns the profile in progress as folded stacks, for flame graph tools.
+    pub fn profile_folded_stacks(&self) -> Option<String> {
+        self.cpu.profiler.as_ref().map(Profiler::folded_stacks)
+    }
+
+    /// Starts tracking how each byte of memory is used, discarding any coverage in progress.
+    pub fn start_coverage(&mut self) {
+        self.cpu.coverage = Some(Coverage::new());
+    }
+
+    /// Returns whether memory coverage is being tracked.
+    pub fn is_tracking_coverage(&self) -> bool {
+        self.cpu.coverage.is_some()
+    }
+
+    /// Returns the coverage flags of every byte of memory, one byte per address.
+    pub fn coverage_map(&self) -> Option<Vec<u8>> {
+        self.cpu
+            .coverage
+            .as_ref()
+            .map(|coverage| coverage.map().to_vec())
+    }
+
+    /// Returns memory as a hex listing annotated with the coverage of each byte.
+    pub fn coverage_listing(&self) -> Option<String> {
+        self.cpu
+            .coverage
+            .as_ref()
+            .map(|coverage| coverage.listing(self.cpu.ram.data()))
+    }
+
+    /// Starts journaling the writes instructions make to memory, discarding any journal in progress.
+    pub fn start_write_journal(&mut self) {
+        self.cpu.journal = Some(WriteJournal::new());
+    }
+
+    /// Returns whether writes to memory are being journaled.
+    pub fn is_journaling_writes(&self) -> bool {
+        self.cpu.journal.is_some()
+    }
+
+    /// Returns the addresses of executed code that has since been overwritten,
+    /// or nothing if writes aren't being journaled.
+    pub fn self_modified_addresses(&self) -> Vec<u16> {
+        self.cpu
+            .journal
+            .as_ref()
+            .map(WriteJournal::self_modified_addresses)
+            .unwrap_or_default()
+    }
+
+    /// Plays back a movie from the state it was recorded from, feeding its key events in at the
+    /// instructions they were recorded at. Playback ends once the last event is applied.
+    /// Fails, leaving the virtual machine untouched, if any state in the movie is invalid.
+    pub fn play(&mut self, movie: Movie) -> Result<(), StateError> {
+        let mut cpu = Cpu::new(0);
+        for checkpoint in movie.checkpoints() {
+            cpu.load_state(&checkpoint.state)?;
+        }
+        self.load_state(movie.state())?;
+        self.key_queue.clear();
+        self.playback = Some(Playback::new(movie));
+        Ok(())
+    }
+
+    /// Stops playing back the current movie.
+    pub fn stop_playback(&mut self) {
+        self.playback = None;
+    }
+
+    /// Returns whether a movie is being played back.
+    pub fn is_playing(&self) -> bool {
+        self.playback.is_some()
     }
 
     /// Resets the virtual machine.
-    /// The seed for the random number generator is not reset.
+    /// The random number generator is not reseeded, use `reseed` for that.
     /// All registers, the stack, timers, ram and the frame buffer are reset.
     /// The font is reloaded... However any program that was in memory is cleared, and will need
     /// to be loaded again.
+    /// A virtual machine that has halted or stopped at a breakpoint runs again,
+    /// one that is paused stays paused.
     pub fn reset(&mut self) {
         self.cpu.reset();
+        if self.run_state == RunState::Breakpoint {
+            self.run_state = RunState::Running;
+        }
+        self.step_over_breakpoint = false;
+        self.restart();
+        self.checkpoint();
+    }
+
+    // records what the host changed while a movie is being recorded
+    fn checkpoint(&mut self) {
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.checkpoint(self.cpu.save_state());
+        }
+    }
+
+    // lets a program that has finished or hung run again, after its state was replaced
+    fn restart(&mut self) {
+        if self.run_state == RunState::Halted {
+            self.run_state = RunState::Running;
+        }
+        if let Some(detector) = self.hang_detector.as_mut() {
+            *detector = HangDetector::new(detector.frames());
+        }
+    }
+}
+
+impl Chip8 {
+    /// Returns the colours of pixels that are on and off.
+    pub fn colors(&self) -> (Color, Color) {
+        self.cpu.frame.colors()
+    }
+
+    /// Sets the colours of pixels that are on and off, which must differ.
+    /// Equal colours are ignored. Returns whether the colours were set.
+    pub fn set_colors(&mut self, on_color: Color, off_color: Color) -> bool {
+        self.cpu.frame.set_colors(on_color, off_color)
+    }
+
+    /// Applies the settings of a database entry: the platform, quirks, speed and colours.
+    pub fn apply_entry(&mut self, entry: &Entry) {
+        self.set_platform(entry.platform);
+        self.set_quirks(entry.quirks);
+        if let Some(speed) = entry.speed() {
+            let _ = self.try_set_speed(speed);
+        }
+        if let Some((on_color, off_color)) = entry.colors {
+            self.set_colors(on_color, off_color);
+        }
+    }
+
+    /// Checks that a program fits both the given platform and the memory layout.
+    pub(crate) fn check_rom_size(&self, rom: &Rom, platform: Platform) -> Result<(), RomError> {
+        let max_size = platform
+            .max_program_size()
+            .min(self.cpu.layout.max_program_size());
+        if rom.len() > max_size {
+            return Err(RomError::TooLarge {
+                size: rom.len(),
+                max_size,
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns the font `FX29` and `FX30` point into.
+    pub fn font(&self) -> Font {
+        self.cpu.font
+    }
+
+    /// Installs a font at the font address of the memory layout, where it stays across resets.
+    /// Fails if the font doesn't fit into memory at that address, or would overlap the memory
+    /// programs are loaded into.
+    pub fn set_font(&mut self, font: Font) -> Result<(), MemoryError> {
+        self.cpu.layout.check(font.size())?;
+        self.cpu.font = font;
+        self.cpu.load_font();
+        self.checkpoint();
+        Ok(())
+    }
+
+    /// Returns the profile in progress.
+    pub fn profiler(&self) -> Option<&Profiler> {
+        self.cpu.profiler.as_ref()
+    }
+
+    /// Stops profiling and returns the profile, if profiling was in progress.
+    pub fn stop_profiling(&mut self) -> Option<Profiler> {
+        self.cpu.profiler.take()
+    }
+
+    /// Takes every event that happened since they were last taken, oldest first.
+    /// Only the most recent 1024 are kept, so hosts should take them at least every frame.
+    pub fn take_events(&mut self) -> Vec<Event> {
+        self.cpu.events.drain(..).collect()
+    }
+
+    /// Returns the memory coverage in progress.
+    pub fn coverage(&self) -> Option<&Coverage> {
+        self.cpu.coverage.as_ref()
+    }
+
+    /// Stops tracking memory coverage and returns it, if tracking was in progress.
+    pub fn stop_coverage(&mut self) -> Option<Coverage> {
+        self.cpu.coverage.take()
+    }
+
+    /// Returns the journal of writes to memory in progress.
+    pub fn write_journal(&self) -> Option<&WriteJournal> {
+        self.cpu.journal.as_ref()
+    }
+
+    /// Stops journaling writes to memory and returns the journal, if journaling was in progress.
+    pub fn stop_write_journal(&mut self) -> Option<WriteJournal> {
+        self.cpu.journal.take()
+    }
+
+    /// Replaces the random number generator used by the `CXNN` instruction.
+    /// The source keeps its own state, so `reseed` should be called afterwards if needed.
+    pub fn set_random_source(&mut self, source: Box<dyn RandomSource>) {
+        self.cpu.rng = source;
+        self.checkpoint();
     }
 }
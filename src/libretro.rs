@@ -0,0 +1,673 @@
+//! A libretro core, behind the `libretro` feature, so that RetroArch and other frontends can
+//! run programs on the virtual machine.
+//!
+//! Only the parts of the libretro API the core needs are declared here. The frontend drives the
+//! core from a single thread, so its state lives in a thread local.
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::ffi::{c_char, c_uint, c_void, CStr};
+use core::{ptr, slice};
+
+use crate::cpu::STACK_SIZE;
+use crate::frame::{FRAME_HEIGHT, FRAME_WIDTH};
+use crate::keypad::{Key, KeyState, KEY_COUNT};
+use crate::platform::Platform;
+use crate::quirks::Quirks;
+use crate::rom::Rom;
+use crate::Chip8;
+
+const RETRO_API_VERSION: c_uint = 1;
+
+const RETRO_ENVIRONMENT_SET_PIXEL_FORMAT: c_uint = 10;
+const RETRO_ENVIRONMENT_SET_INPUT_DESCRIPTORS: c_uint = 11;
+const RETRO_ENVIRONMENT_GET_VARIABLE: c_uint = 15;
+const RETRO_ENVIRONMENT_SET_VARIABLES: c_uint = 16;
+const RETRO_ENVIRONMENT_GET_VARIABLE_UPDATE: c_uint = 17;
+const RETRO_ENVIRONMENT_SET_SUPPORT_NO_GAME: c_uint = 18;
+
+const RETRO_PIXEL_FORMAT_XRGB8888: c_uint = 1;
+
+const RETRO_DEVICE_JOYPAD: c_uint = 1;
+const RETRO_DEVICE_KEYBOARD: c_uint = 3;
+
+const RETRO_DEVICE_ID_JOYPAD_B: c_uint = 0;
+const RETRO_DEVICE_ID_JOYPAD_Y: c_uint = 1;
+const RETRO_DEVICE_ID_JOYPAD_SELECT: c_uint = 2;
+const RETRO_DEVICE_ID_JOYPAD_START: c_uint = 3;
+const RETRO_DEVICE_ID_JOYPAD_UP: c_uint = 4;
+const RETRO_DEVICE_ID_JOYPAD_DOWN: c_uint = 5;
+const RETRO_DEVICE_ID_JOYPAD_LEFT: c_uint = 6;
+const RETRO_DEVICE_ID_JOYPAD_RIGHT: c_uint = 7;
+const RETRO_DEVICE_ID_JOYPAD_A: c_uint = 8;
+const RETRO_DEVICE_ID_JOYPAD_X: c_uint = 9;
+const RETRO_DEVICE_ID_JOYPAD_L: c_uint = 10;
+const RETRO_DEVICE_ID_JOYPAD_R: c_uint = 11;
+
+const RETRO_REGION_NTSC: c_uint = 0;
+const RETRO_MEMORY_SYSTEM_RAM: c_uint = 2;
+
+const FRAMES_PER_SECOND: u32 = 60;
+const FRAME_MICRO_SECONDS: u32 = 1_000_000 / FRAMES_PER_SECOND;
+const SAMPLE_RATE: u32 = 44_100;
+const SAMPLES_PER_FRAME: usize = (SAMPLE_RATE / FRAMES_PER_SECOND) as usize;
+const BUZZER_FREQUENCY: u32 = 440;
+const BUZZER_VOLUME: i16 = 0x1000;
+// the length states are stored behind, as a 32 bit little endian number
+const STATE_LENGTH_SIZE: usize = 4;
+
+// the keys under the d-pad and face buttons are the ones most programs use for movement and actions
+const JOYPAD_BINDINGS: [(c_uint, Key, &CStr); 12] = [
+    (RETRO_DEVICE_ID_JOYPAD_UP, Key::Key5, c"Up (5)"),
+    (RETRO_DEVICE_ID_JOYPAD_DOWN, Key::Key8, c"Down (8)"),
+    (RETRO_DEVICE_ID_JOYPAD_LEFT, Key::Key7, c"Left (7)"),
+    (RETRO_DEVICE_ID_JOYPAD_RIGHT, Key::Key9, c"Right (9)"),
+    (RETRO_DEVICE_ID_JOYPAD_A, Key::Key6, c"6"),
+    (RETRO_DEVICE_ID_JOYPAD_B, Key::Key4, c"4"),
+    (RETRO_DEVICE_ID_JOYPAD_X, Key::Key2, c"2"),
+    (RETRO_DEVICE_ID_JOYPAD_Y, Key::Key1, c"1"),
+    (RETRO_DEVICE_ID_JOYPAD_L, Key::Key3, c"3"),
+    (RETRO_DEVICE_ID_JOYPAD_R, Key::KeyC, c"C"),
+    (RETRO_DEVICE_ID_JOYPAD_SELECT, Key::Key0, c"0"),
+    (RETRO_DEVICE_ID_JOYPAD_START, Key::KeyF, c"F"),
+];
+
+const SPEED_OPTION: &CStr = c"chip8_core_speed";
+const PLATFORM_OPTION: &CStr = c"chip8_core_platform";
+const QUIRK_OPTIONS: [&CStr; 6] = [
+    c"chip8_core_quirk_shift",
+    c"chip8_core_quirk_load_store",
+    c"chip8_core_quirk_clip",
+    c"chip8_core_quirk_vblank",
+    c"chip8_core_quirk_jump",
+    c"chip8_core_quirk_logic",
+];
+
+// the first value of each option is its default, "auto" keeps what the database recommends
+const OPTIONS: [(&CStr, &CStr); 8] = [
+    (
+        SPEED_OPTION,
+        c"Speed (instructions per second); auto|500|600|700|800|1000|1200|1500|2000|3000|5000|10000",
+    ),
+    (PLATFORM_OPTION, c"Platform; auto|modern|vip|schip"),
+    (
+        QUIRK_OPTIONS[0],
+        c"Shift quirk (8XY6 and 8XYE shift VX); auto|enabled|disabled",
+    ),
+    (
+        QUIRK_OPTIONS[1],
+        c"Load/store quirk (FX55 and FX65 leave I); auto|enabled|disabled",
+    ),
+    (
+        QUIRK_OPTIONS[2],
+        c"Clip quirk (sprites clip at the edges); auto|enabled|disabled",
+    ),
+    (
+        QUIRK_OPTIONS[3],
+        c"VBlank quirk (DXYN waits for the display); auto|enabled|disabled",
+    ),
+    (
+        QUIRK_OPTIONS[4],
+        c"Jump quirk (BNNN jumps to NNN plus VX); auto|enabled|disabled",
+    ),
+    (
+        QUIRK_OPTIONS[5],
+        c"Logic quirk (8XY1, 8XY2 and 8XY3 reset VF); auto|enabled|disabled",
+    ),
+];
+
+pub type RetroEnvironment = unsafe extern "C" fn(cmd: c_uint, data: *mut c_void) -> bool;
+pub type RetroVideoRefresh =
+    unsafe extern "C" fn(data: *const c_void, width: c_uint, height: c_uint, pitch: usize);
+pub type RetroAudioSample = unsafe extern "C" fn(left: i16, right: i16);
+pub type RetroAudioSampleBatch = unsafe extern "C" fn(data: *const i16, frames: usize) -> usize;
+pub type RetroInputPoll = unsafe extern "C" fn();
+pub type RetroInputState =
+    unsafe extern "C" fn(port: c_uint, device: c_uint, index: c_uint, id: c_uint) -> i16;
+
+#[repr(C)]
+pub struct RetroSystemInfo {
+    pub library_name: *const c_char,
+    pub library_version: *const c_char,
+    pub valid_extensions: *const c_char,
+    pub need_fullpath: bool,
+    pub block_extract: bool,
+}
+
+#[repr(C)]
+pub struct RetroGameGeometry {
+    pub base_width: c_uint,
+    pub base_height: c_uint,
+    pub max_width: c_uint,
+    pub max_height: c_uint,
+    pub aspect_ratio: f32,
+}
+
+#[repr(C)]
+pub struct RetroSystemTiming {
+    pub fps: f64,
+    pub sample_rate: f64,
+}
+
+#[repr(C)]
+pub struct RetroSystemAvInfo {
+    pub geometry: RetroGameGeometry,
+    pub timing: RetroSystemTiming,
+}
+
+#[repr(C)]
+pub struct RetroGameInfo {
+    pub path: *const c_char,
+    pub data: *const c_void,
+    pub size: usize,
+    pub meta: *const c_char,
+}
+
+#[repr(C)]
+pub struct RetroVariable {
+    pub key: *const c_char,
+    pub value: *const c_char,
+}
+
+#[repr(C)]
+pub struct RetroInputDescriptor {
+    pub port: c_uint,
+    pub device: c_uint,
+    pub index: c_uint,
+    pub id: c_uint,
+    pub description: *const c_char,
+}
+
+#[derive(Default)]
+struct Callbacks {
+    environment: Option<RetroEnvironment>,
+    video_refresh: Option<RetroVideoRefresh>,
+    audio_sample_batch: Option<RetroAudioSampleBatch>,
+    input_poll: Option<RetroInputPoll>,
+    input_state: Option<RetroInputState>,
+}
+
+struct Core {
+    chip8: Chip8,
+    rom: Rom,
+    // the settings the database recommends, which options left on "auto" fall back to
+    recommended: (u32, Platform, Quirks),
+    pressed: [bool; KEY_COUNT],
+    video: Vec<u32>,
+    audio: Vec<i16>,
+    audio_phase: u32,
+    // memory as it was after the last frame, kept once memory is handed to the frontend,
+    // which may write to it between any two frames
+    memory_snapshot: Option<Vec<u8>>,
+}
+
+#[derive(Default)]
+struct State {
+    callbacks: Callbacks,
+    core: Option<Core>,
+}
+
+std::thread_local! {
+    static STATE: RefCell<State> = RefCell::new(State::default());
+}
+
+#[no_mangle]
+pub extern "C" fn retro_api_version() -> c_uint {
+    RETRO_API_VERSION
+}
+
+/// # Safety
+/// `environment` must stay callable until the core is deinitialised.
+#[no_mangle]
+pub unsafe extern "C" fn retro_set_environment(environment: RetroEnvironment) {
+    STATE.with_borrow_mut(|state| state.callbacks.environment = Some(environment));
+
+    let variables: Vec<RetroVariable> = OPTIONS
+        .iter()
+        .map(|(key, value)| RetroVariable {
+            key: key.as_ptr(),
+            value: value.as_ptr(),
+        })
+        .chain([RetroVariable {
+            key: ptr::null(),
+            value: ptr::null(),
+        }])
+        .collect();
+    environment(
+        RETRO_ENVIRONMENT_SET_VARIABLES,
+        variables.as_ptr() as *mut c_void,
+    );
+    let mut no_game = false;
+    environment(
+        RETRO_ENVIRONMENT_SET_SUPPORT_NO_GAME,
+        &mut no_game as *mut bool as *mut c_void,
+    );
+}
+
+#[no_mangle]
+pub extern "C" fn retro_set_video_refresh(video_refresh: RetroVideoRefresh) {
+    STATE.with_borrow_mut(|state| state.callbacks.video_refresh = Some(video_refresh));
+}
+
+// every frame of audio is sent in one batch, so single samples are never used
+#[no_mangle]
+pub extern "C" fn retro_set_audio_sample(_audio_sample: RetroAudioSample) {}
+
+#[no_mangle]
+pub extern "C" fn retro_set_audio_sample_batch(audio_sample_batch: RetroAudioSampleBatch) {
+    STATE.with_borrow_mut(|state| state.callbacks.audio_sample_batch = Some(audio_sample_batch));
+}
+
+#[no_mangle]
+pub extern "C" fn retro_set_input_poll(input_poll: RetroInputPoll) {
+    STATE.with_borrow_mut(|state| state.callbacks.input_poll = Some(input_poll));
+}
+
+#[no_mangle]
+pub extern "C" fn retro_set_input_state(input_state: RetroInputState) {
+    STATE.with_borrow_mut(|state| state.callbacks.input_state = Some(input_state));
+}
+
+#[no_mangle]
+pub extern "C" fn retro_init() {}
+
+#[no_mangle]
+pub extern "C" fn retro_deinit() {
+    STATE.with_borrow_mut(|state| *state = State::default());
+}
+
+/// # Safety
+/// `info` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn retro_get_system_info(info: *mut RetroSystemInfo) {
+    info.write(RetroSystemInfo {
+        library_name: c"chip8_core".as_ptr(),
+        library_version: concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const c_char,
+        valid_extensions: c"ch8|c8|sc8|rom".as_ptr(),
+        need_fullpath: false,
+        block_extract: false,
+    });
+}
+
+/// # Safety
+/// `info` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn retro_get_system_av_info(info: *mut RetroSystemAvInfo) {
+    info.write(RetroSystemAvInfo {
+        geometry: RetroGameGeometry {
+            base_width: FRAME_WIDTH as c_uint,
+            base_height: FRAME_HEIGHT as c_uint,
+            max_width: FRAME_WIDTH as c_uint,
+            max_height: FRAME_HEIGHT as c_uint,
+            aspect_ratio: FRAME_WIDTH as f32 / FRAME_HEIGHT as f32,
+        },
+        timing: RetroSystemTiming {
+            fps: FRAMES_PER_SECOND as f64,
+            sample_rate: SAMPLE_RATE as f64,
+        },
+    });
+}
+
+#[no_mangle]
+pub extern "C" fn retro_set_controller_port_device(_port: c_uint, _device: c_uint) {}
+
+/// Loads a program, applying the settings the database recommends and then the core options.
+///
+/// # Safety
+/// `game` must be null or point to a game info whose data is `size` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn retro_load_game(game: *const RetroGameInfo) -> bool {
+    let Some(game) = game.as_ref() else {
+        return false;
+    };
+    if game.data.is_null() {
+        return false;
+    }
+    let bytes = slice::from_raw_parts(game.data as *const u8, game.size);
+    let Ok(rom) = Rom::new(bytes) else {
+        return false;
+    };
+
+    let mut chip8 = Chip8::new(seed());
+    chip8.apply_recommended_settings(&rom);
+    let recommended = (chip8.speed(), chip8.platform(), chip8.quirks());
+    let mut core = Core {
+        chip8,
+        rom,
+        recommended,
+        pressed: [false; KEY_COUNT],
+        video: vec![0; FRAME_WIDTH * FRAME_HEIGHT],
+        audio: vec![0; SAMPLES_PER_FRAME * 2],
+        audio_phase: 0,
+        memory_snapshot: None,
+    };
+
+    STATE.with_borrow_mut(|state| {
+        let Some(environment) = state.callbacks.environment else {
+            return false;
+        };
+        let mut pixel_format = RETRO_PIXEL_FORMAT_XRGB8888;
+        if !environment(
+            RETRO_ENVIRONMENT_SET_PIXEL_FORMAT,
+            &mut pixel_format as *mut c_uint as *mut c_void,
+        ) {
+            return false;
+        }
+        let descriptors: Vec<RetroInputDescriptor> = JOYPAD_BINDINGS
+            .iter()
+            .map(|&(id, _, description)| RetroInputDescriptor {
+                port: 0,
+                device: RETRO_DEVICE_JOYPAD,
+                index: 0,
+                id,
+                description: description.as_ptr(),
+            })
+            .chain([RetroInputDescriptor {
+                port: 0,
+                device: 0,
+                index: 0,
+                id: 0,
+                description: ptr::null(),
+            }])
+            .collect();
+        environment(
+            RETRO_ENVIRONMENT_SET_INPUT_DESCRIPTORS,
+            descriptors.as_ptr() as *mut c_void,
+        );
+
+        core.apply_options(environment);
+        if core.chip8.load_rom(&core.rom).is_err() {
+            return false;
+        }
+        state.core = Some(core);
+        true
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn retro_load_game_special(
+    _game_type: c_uint,
+    _info: *const RetroGameInfo,
+    _num_info: usize,
+) -> bool {
+    false
+}
+
+#[no_mangle]
+pub extern "C" fn retro_unload_game() {
+    STATE.with_borrow_mut(|state| state.core = None);
+}
+
+#[no_mangle]
+pub extern "C" fn retro_get_region() -> c_uint {
+    RETRO_REGION_NTSC
+}
+
+/// Resets the virtual machine and loads the program again.
+#[no_mangle]
+pub extern "C" fn retro_reset() {
+    STATE.with_borrow_mut(|state| {
+        if let Some(core) = state.core.as_mut() {
+            core.chip8.reset();
+            core.pressed = [false; KEY_COUNT];
+            // the program fitted when it was first loaded, and the layout can't change since
+            let _ = core.chip8.load_rom(&core.rom);
+        }
+    });
+}
+
+/// Runs the virtual machine for one frame, then hands the frame and its audio to the frontend.
+///
+/// # Safety
+/// The callbacks given by the frontend must still be callable.
+#[no_mangle]
+pub unsafe extern "C" fn retro_run() {
+    STATE.with_borrow_mut(|state| {
+        let State { callbacks, core } = state;
+        let Some(core) = core.as_mut() else {
+            return;
+        };
+
+        if let Some(environment) = callbacks.environment {
+            let mut updated = false;
+            environment(
+                RETRO_ENVIRONMENT_GET_VARIABLE_UPDATE,
+                &mut updated as *mut bool as *mut c_void,
+            );
+            if updated {
+                core.apply_options(environment);
+            }
+        }
+        if let (Some(input_poll), Some(input_state)) = (callbacks.input_poll, callbacks.input_state)
+        {
+            input_poll();
+            core.poll_keys(input_state);
+        }
+
+        // the frontend may have written to memory through `retro_get_memory_data` since the last frame
+        if let Some(snapshot) = core.memory_snapshot.as_mut() {
+            core.chip8.cpu.ram.invalidate_changes(snapshot);
+        }
+        core.chip8.update(FRAME_MICRO_SECONDS);
+        if let Some(snapshot) = core.memory_snapshot.as_mut() {
+            snapshot.copy_from_slice(core.chip8.cpu.ram.data());
+        }
+
+        if let Some(video_refresh) = callbacks.video_refresh {
+            core.render_video();
+            video_refresh(
+                core.video.as_ptr() as *const c_void,
+                FRAME_WIDTH as c_uint,
+                FRAME_HEIGHT as c_uint,
+                FRAME_WIDTH * 4,
+            );
+        }
+        if let Some(audio_sample_batch) = callbacks.audio_sample_batch {
+            core.render_audio();
+            audio_sample_batch(core.audio.as_ptr(), SAMPLES_PER_FRAME);
+        }
+    });
+}
+
+/// The size of a state with the stack full, since frontends expect the size not to change.
+/// States are stored behind their length and padded up to it.
+#[no_mangle]
+pub extern "C" fn retro_serialize_size() -> usize {
+    STATE.with_borrow(|state| {
+        state.core.as_ref().map_or(0, |core| {
+            let stack_free = STACK_SIZE - core.chip8.cpu.registers.sp as usize;
+            STATE_LENGTH_SIZE + core.chip8.save_state().len() + stack_free * 2
+        })
+    })
+}
+
+/// # Safety
+/// `data` must point to `size` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn retro_serialize(data: *mut c_void, size: usize) -> bool {
+    STATE.with_borrow(|state| {
+        let Some(core) = state.core.as_ref() else {
+            return false;
+        };
+        let bytes = core.chip8.save_state();
+        if data.is_null() || STATE_LENGTH_SIZE + bytes.len() > size {
+            return false;
+        }
+        let data = slice::from_raw_parts_mut(data as *mut u8, size);
+        let (length, rest) = data.split_at_mut(STATE_LENGTH_SIZE);
+        length.copy_from_slice(&(bytes.len() as u32).to_le_bytes());
+        let (state, padding) = rest.split_at_mut(bytes.len());
+        state.copy_from_slice(&bytes);
+        padding.fill(0);
+        true
+    })
+}
+
+/// # Safety
+/// `data` must point to `size` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn retro_unserialize(data: *const c_void, size: usize) -> bool {
+    if data.is_null() {
+        return false;
+    }
+    let bytes = slice::from_raw_parts(data as *const u8, size);
+    let Some((length, rest)) = bytes.split_first_chunk::<STATE_LENGTH_SIZE>() else {
+        return false;
+    };
+    let Some(bytes) = rest.get(..u32::from_le_bytes(*length) as usize) else {
+        return false;
+    };
+    STATE.with_borrow_mut(|state| match state.core.as_mut() {
+        Some(core) => core.chip8.load_state(bytes).is_ok(),
+        None => false,
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn retro_cheat_reset() {}
+
+#[no_mangle]
+pub extern "C" fn retro_cheat_set(_index: c_uint, _enabled: bool, _code: *const c_char) {}
+
+/// Exposes memory as system RAM, so that frontends can search it and apply cheats.
+#[no_mangle]
+pub extern "C" fn retro_get_memory_data(id: c_uint) -> *mut c_void {
+    STATE.with_borrow_mut(|state| match (id, state.core.as_mut()) {
+        (RETRO_MEMORY_SYSTEM_RAM, Some(core)) => {
+            core.memory_snapshot = Some(core.chip8.cpu.ram.data().to_vec());
+            core.chip8.cpu.ram.data_mut().as_mut_ptr() as *mut c_void
+        }
+        _ => ptr::null_mut(),
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn retro_get_memory_size(id: c_uint) -> usize {
+    STATE.with_borrow(|state| match (id, state.core.as_ref()) {
+        (RETRO_MEMORY_SYSTEM_RAM, Some(core)) => core.chip8.cpu.ram.data().len(),
+        _ => 0,
+    })
+}
+
+impl Core {
+    unsafe fn apply_options(&mut self, environment: RetroEnvironment) {
+        let (speed, platform, quirks) = self.recommended;
+        let speed = get_variable(environment, SPEED_OPTION)
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(speed);
+        let platform = match get_variable(environment, PLATFORM_OPTION).as_deref() {
+            Some("vip") => Platform::Vip,
+            Some("modern") => Platform::Modern,
+            Some("schip") => Platform::Schip,
+            _ => platform,
+        };
+        // choosing a platform brings its quirks, unless they are set individually
+        let mut quirks = match get_variable(environment, PLATFORM_OPTION).as_deref() {
+            Some("auto") | None => quirks,
+            Some(_) => Quirks::for_platform(platform),
+        };
+        let fields = [
+            &mut quirks.shift,
+            &mut quirks.load_store,
+            &mut quirks.clip,
+            &mut quirks.vblank,
+            &mut quirks.jump,
+            &mut quirks.logic,
+        ];
+        for (option, quirk) in QUIRK_OPTIONS.iter().zip(fields) {
+            match get_variable(environment, option).as_deref() {
+                Some("enabled") => *quirk = true,
+                Some("disabled") => *quirk = false,
+                _ => (),
+            }
+        }
+
+        // speeds outside the range the virtual machine runs at are ignored
+        let _ = self.chip8.try_set_speed(speed);
+        self.chip8.set_platform(platform);
+        self.chip8.set_quirks(quirks);
+    }
+
+    // a key is held if it is held on the joypad or on the keyboard, through the key map
+    unsafe fn poll_keys(&mut self, input_state: RetroInputState) {
+        let mut pressed = [false; KEY_COUNT];
+        for &(id, key, _) in JOYPAD_BINDINGS.iter() {
+            if input_state(0, RETRO_DEVICE_JOYPAD, 0, id) != 0 {
+                pressed[u8::from(key) as usize] = true;
+            }
+        }
+        let key_map = self.chip8.key_map();
+        for (host_key, key) in key_map.iter() {
+            // libretro key codes match ASCII for printable characters
+            let mut chars = host_key.chars();
+            if let (Some(char), None) = (chars.next(), chars.next()) {
+                if char.is_ascii() && input_state(0, RETRO_DEVICE_KEYBOARD, 0, char as c_uint) != 0
+                {
+                    pressed[u8::from(key) as usize] = true;
+                }
+            }
+        }
+
+        for (index, (&now, before)) in pressed.iter().zip(self.pressed.iter_mut()).enumerate() {
+            if now != *before {
+                let state = if now {
+                    KeyState::Pressed
+                } else {
+                    KeyState::Released
+                };
+                self.chip8.handle_key_event((index as u8).into(), state);
+                *before = now;
+            }
+        }
+    }
+
+    // the frontend expects each pixel as 0x00RRGGBB
+    fn render_video(&mut self) {
+        let frame = &self.chip8.cpu.frame.buffer;
+        for (pixel, rgba) in self.video.iter_mut().zip(frame.chunks_exact(4)) {
+            *pixel = u32::from_be_bytes([0, rgba[0], rgba[1], rgba[2]]);
+        }
+    }
+
+    // a square wave sounds while the sound timer is running
+    fn render_audio(&mut self) {
+        let period = SAMPLE_RATE / BUZZER_FREQUENCY;
+        let is_sounding = self.chip8.registers().st > 0;
+        for frame in self.audio.chunks_exact_mut(2) {
+            let sample = match is_sounding {
+                true if self.audio_phase < period / 2 => BUZZER_VOLUME,
+                true => -BUZZER_VOLUME,
+                false => 0,
+            };
+            frame.fill(sample);
+            self.audio_phase = (self.audio_phase + 1) % period;
+        }
+    }
+}
+
+unsafe fn get_variable(environment: RetroEnvironment, key: &CStr) -> Option<String> {
+    let mut variable = RetroVariable {
+        key: key.as_ptr(),
+        value: ptr::null(),
+    };
+    if !environment(
+        RETRO_ENVIRONMENT_GET_VARIABLE,
+        &mut variable as *mut RetroVariable as *mut c_void,
+    ) || variable.value.is_null()
+    {
+        return None;
+    }
+    CStr::from_ptr(variable.value)
+        .to_str()
+        .ok()
+        .map(String::from)
+}
+
+// seeds the random number generator from the clock, save states carry its state from then on
+fn seed() -> u32 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |time| time.subsec_nanos())
+}
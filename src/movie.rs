@@ -0,0 +1,341 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::keypad::{Key, KeyState};
+
+const MAGIC: &[u8; 4] = b"C8MV";
+const VERSION: u8 = 2;
+// marks a checkpoint in place of a key state, in the low nibble of an entry
+const CHECKPOINT_MARKER: u8 = 0xf;
+
+/// A key event captured in a movie.
+/// The cycle is the number of instructions executed since the recording started.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InputEvent {
+    pub cycle: u64,
+    pub key: Key,
+    pub state: KeyState,
+}
+
+/// A state the host put the recorded virtual machine into between two instructions,
+/// such as by changing its speed or quirks, captured as a saved state.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Checkpoint {
+    pub cycle: u64,
+    pub state: Vec<u8>,
+}
+
+/// A recording of every key event passed to a virtual machine, starting from a saved state of it,
+/// so that the session can be replayed exactly.
+/// Anything the host changes while recording, such as the speed, is kept as a checkpoint.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Movie {
+    state: Vec<u8>,
+    events: Vec<InputEvent>,
+    checkpoints: Vec<Checkpoint>,
+}
+
+/// Errors that can occur when decoding a movie.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MovieError {
+    InvalidHeader,
+    UnsupportedVersion(u8),
+    UnexpectedEnd,
+    InvalidEvent,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Movie {
+    /// Decodes a movie from the bytes produced by `to_bytes`.
+    /// The states it holds are only checked once it is played.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Movie, MovieError> {
+        let mut reader = bytes;
+        if take(&mut reader, MAGIC.len()).ok() != Some(MAGIC.as_slice()) {
+            return Err(MovieError::InvalidHeader);
+        }
+        let version = take(&mut reader, 1)?[0];
+        if version != VERSION {
+            return Err(MovieError::UnsupportedVersion(version));
+        }
+        let state = read_state(&mut reader)?;
+        let count = read_u32(&mut reader)? as usize;
+
+        let mut events = Vec::with_capacity(count.min(reader.len() / 2));
+        let mut checkpoints = Vec::new();
+        let mut cycle: u64 = 0;
+        for _ in 0..count {
+            let delta = read_varint(&mut reader)?;
+            let byte = take(&mut reader, 1)?[0];
+            cycle = cycle.checked_add(delta).ok_or(MovieError::InvalidEvent)?;
+            if byte & 0xf == CHECKPOINT_MARKER {
+                let state = read_state(&mut reader)?;
+                checkpoints.push(Checkpoint { cycle, state });
+            } else {
+                events.push(InputEvent {
+                    cycle,
+                    key: (byte >> 4).into(),
+                    state: decode_state(byte & 0xf)?,
+                });
+            }
+        }
+        if !reader.is_empty() {
+            return Err(MovieError::InvalidEvent);
+        }
+        Ok(Self {
+            state,
+            events,
+            checkpoints,
+        })
+    }
+
+    /// Encodes the movie into its compact binary form.
+    ///
+    /// The format is `C8MV` and a version byte, the saved state recording started from and
+    /// the number of entries, followed by each entry in the order they occurred. An entry is the
+    /// cycles since the previous entry in LEB128 and a byte holding the key in the high nibble and
+    /// the state in the low nibble, or `0xF` in the low nibble for a checkpoint followed by its
+    /// saved state. Saved states are prefixed by their size, and numbers are little endian.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(
+            self.state.len() + self.events.len() * 3 + self.checkpoints.len() * self.state.len(),
+        );
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        write_state(&mut bytes, &self.state);
+        let count = self.events.len() + self.checkpoints.len();
+        bytes.extend_from_slice(&(count as u32).to_le_bytes());
+
+        let mut previous_cycle = 0;
+        let mut events = self.events.iter().peekable();
+        let mut checkpoints = self.checkpoints.iter().peekable();
+        loop {
+            // a checkpoint comes before the key events of the same cycle, as it did when recorded
+            let checkpoint_first = match (checkpoints.peek(), events.peek()) {
+                (Some(checkpoint), Some(event)) => checkpoint.cycle <= event.cycle,
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (None, None) => break,
+            };
+            if checkpoint_first {
+                let checkpoint = checkpoints.next().unwrap();
+                write_varint(&mut bytes, checkpoint.cycle - previous_cycle);
+                bytes.push(CHECKPOINT_MARKER);
+                write_state(&mut bytes, &checkpoint.state);
+                previous_cycle = checkpoint.cycle;
+            } else {
+                let event = events.next().unwrap();
+                write_varint(&mut bytes, event.cycle - previous_cycle);
+                bytes.push((u8::from(event.key) << 4) | encode_state(event.state));
+                previous_cycle = event.cycle;
+            }
+        }
+        bytes
+    }
+
+    /// Returns the number of recorded key events.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Returns whether no key events were recorded.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+impl Movie {
+    pub(crate) fn new(state: Vec<u8>) -> Self {
+        Self {
+            state,
+            events: Vec::new(),
+            checkpoints: Vec::new(),
+        }
+    }
+
+    /// Returns the saved state the recording started from.
+    pub fn state(&self) -> &[u8] {
+        &self.state
+    }
+
+    /// Returns the recorded key events, in the order they occurred.
+    pub fn events(&self) -> &[InputEvent] {
+        &self.events
+    }
+
+    /// Returns the checkpoints, in the order they occurred.
+    pub fn checkpoints(&self) -> &[Checkpoint] {
+        &self.checkpoints
+    }
+}
+
+/// Tracks the progress of a movie being recorded.
+pub(crate) struct Recorder {
+    movie: Movie,
+    cycles: u64,
+}
+
+impl Recorder {
+    pub fn new(state: Vec<u8>) -> Self {
+        Self {
+            movie: Movie::new(state),
+            cycles: 0,
+        }
+    }
+
+    pub fn record(&mut self, key: Key, state: KeyState) {
+        self.movie.events.push(InputEvent {
+            cycle: self.cycles,
+            key,
+            state,
+        });
+    }
+
+    /// Records the state the host left the virtual machine in, replacing any checkpoint
+    /// from the same cycle, which it supersedes.
+    pub fn checkpoint(&mut self, state: Vec<u8>) {
+        let checkpoints = &mut self.movie.checkpoints;
+        match checkpoints.last_mut() {
+            Some(checkpoint) if checkpoint.cycle == self.cycles => checkpoint.state = state,
+            _ => checkpoints.push(Checkpoint {
+                cycle: self.cycles,
+                state,
+            }),
+        }
+    }
+
+    pub fn step(&mut self) {
+        self.cycles += 1;
+    }
+
+    pub fn skip(&mut self, cycles: u32) {
+        self.cycles += cycles as u64;
+    }
+
+    pub fn finish(self) -> Movie {
+        self.movie
+    }
+}
+
+/// Tracks the progress of a movie being played back.
+pub(crate) struct Playback {
+    movie: Movie,
+    cycles: u64,
+    next_event: usize,
+    next_checkpoint: usize,
+}
+
+impl Playback {
+    pub fn new(movie: Movie) -> Self {
+        Self {
+            movie,
+            cycles: 0,
+            next_event: 0,
+            next_checkpoint: 0,
+        }
+    }
+
+    /// Returns the state of the checkpoint due before the upcoming instruction, if any.
+    pub fn due_checkpoint(&mut self) -> Option<&[u8]> {
+        let checkpoint = self.movie.checkpoints.get(self.next_checkpoint)?;
+        if checkpoint.cycle > self.cycles {
+            return None;
+        }
+        self.next_checkpoint += 1;
+        Some(&checkpoint.state)
+    }
+
+    /// Returns the next event that is due before the upcoming instruction, if any.
+    pub fn next_due_event(&mut self) -> Option<InputEvent> {
+        let event = *self.movie.events.get(self.next_event)?;
+        if event.cycle > self.cycles {
+            return None;
+        }
+        self.next_event += 1;
+        Some(event)
+    }
+
+    pub fn step(&mut self) {
+        self.cycles += 1;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next_event >= self.movie.events.len()
+            && self.next_checkpoint >= self.movie.checkpoints.len()
+    }
+}
+
+impl fmt::Display for MovieError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MovieError::InvalidHeader => write!(f, "invalid movie header"),
+            MovieError::UnsupportedVersion(version) => {
+                write!(f, "unsupported movie version: {}", version)
+            }
+            MovieError::UnexpectedEnd => write!(f, "unexpected end of movie data"),
+            MovieError::InvalidEvent => write!(f, "invalid movie event"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MovieError {}
+
+fn encode_state(state: KeyState) -> u8 {
+    state.into()
+}
+
+fn decode_state(value: u8) -> Result<KeyState, MovieError> {
+    KeyState::try_from(value).map_err(|_| MovieError::InvalidEvent)
+}
+
+fn take<'a>(reader: &mut &'a [u8], size: usize) -> Result<&'a [u8], MovieError> {
+    if reader.len() < size {
+        return Err(MovieError::UnexpectedEnd);
+    }
+    let (taken, rest) = reader.split_at(size);
+    *reader = rest;
+    Ok(taken)
+}
+
+fn read_u32(reader: &mut &[u8]) -> Result<u32, MovieError> {
+    Ok(u32::from_le_bytes(take(reader, 4)?.try_into().unwrap()))
+}
+
+fn read_state(reader: &mut &[u8]) -> Result<Vec<u8>, MovieError> {
+    let size = read_u32(reader)? as usize;
+    Ok(take(reader, size)?.to_vec())
+}
+
+fn write_state(bytes: &mut Vec<u8>, state: &[u8]) {
+    bytes.extend_from_slice(&(state.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(state);
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes.push(byte);
+            return;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+fn read_varint(reader: &mut &[u8]) -> Result<u64, MovieError> {
+    let mut value: u64 = 0;
+    for shift in (0..u64::BITS).step_by(7) {
+        let (&byte, rest) = reader.split_first().ok_or(MovieError::UnexpectedEnd)?;
+        *reader = rest;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(MovieError::InvalidEvent)
+}
@@ -0,0 +1,1265 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+const PROGRAM_START: usize = 0x200;
+const ADDRESS_SPACE: usize = 0x10000;
+// the opcode Octo places at the program start when `main` isn't the first thing assembled
+const MAIN_JUMP_SIZE: usize = 2;
+
+/// Error returned when an Octo program cannot be assembled.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AssemblyError {
+    /// The line the error was found on, counting from 1.
+    pub line: usize,
+    message: String,
+}
+
+/// Assembles a program written in Octo's assembly language into the bytes to load at `0x200`.
+///
+/// Labels, constants, aliases, macros, string modes, `:calc` expressions and the structured
+/// `if`, `loop` and `while` statements are supported, along with the SUPER-CHIP and XO-CHIP
+/// instructions. As in Octo, a program whose first label isn't `main` starts with a jump to it.
+pub fn assemble(source: &str) -> Result<Vec<u8>, AssemblyError> {
+    Assembler::new(tokenize(source)?).run()
+}
+
+#[derive(Clone, Debug)]
+struct Token {
+    text: String,
+    is_string: bool,
+    line: usize,
+}
+
+impl Token {
+    fn is(&self, text: &str) -> bool {
+        !self.is_string && self.text == text
+    }
+}
+
+struct Macro {
+    arguments: Vec<String>,
+    body: Vec<Token>,
+    calls: u32,
+}
+
+// where a label used before its definition is patched in once it is known
+enum FixupKind {
+    // the low twelve bits of the opcode at the address
+    Address,
+    // the two bytes at the address
+    Word,
+    // the low bytes of the two `v0 :=` and `v1 :=` opcodes at the address, after the nibble
+    Unpack(Option<u8>),
+}
+
+struct Fixup {
+    name: String,
+    address: usize,
+    kind: FixupKind,
+    line: usize,
+}
+
+struct Loop {
+    start: usize,
+    breaks: Vec<usize>,
+    line: usize,
+}
+
+#[derive(Clone, Copy)]
+enum Operand {
+    Register(u8),
+    Byte(u8),
+}
+
+#[derive(Clone, Copy)]
+enum TestKind {
+    Equal(Operand),
+    NotEqual(Operand),
+    Key,
+    NotKey,
+}
+
+// a condition reduced to one the skip instructions can test
+#[derive(Clone, Copy)]
+struct Test {
+    x: u8,
+    kind: TestKind,
+}
+
+struct Assembler {
+    tokens: Vec<Token>,
+    position: usize,
+    line: usize,
+    rom: Vec<u8>,
+    used: Vec<bool>,
+    here: usize,
+    end: usize,
+    has_main: bool,
+    constants: BTreeMap<String, f64>,
+    labels: BTreeSet<String>,
+    aliases: BTreeMap<String, u8>,
+    macros: BTreeMap<String, Macro>,
+    string_modes: BTreeMap<String, BTreeMap<char, (usize, Vec<Token>)>>,
+    fixups: Vec<Fixup>,
+    branches: Vec<(usize, usize)>,
+    loops: Vec<Loop>,
+}
+
+impl Assembler {
+    fn new(tokens: Vec<Token>) -> Self {
+        Self {
+            tokens,
+            position: 0,
+            line: 1,
+            rom: vec![0; ADDRESS_SPACE],
+            used: vec![false; ADDRESS_SPACE],
+            here: PROGRAM_START + MAIN_JUMP_SIZE,
+            end: 0,
+            has_main: true,
+            constants: BTreeMap::new(),
+            labels: BTreeSet::new(),
+            aliases: BTreeMap::new(),
+            macros: BTreeMap::new(),
+            string_modes: BTreeMap::new(),
+            fixups: Vec::new(),
+            branches: Vec::new(),
+            loops: Vec::new(),
+        }
+    }
+
+    fn run(mut self) -> Result<Vec<u8>, AssemblyError> {
+        while self.position < self.tokens.len() {
+            self.statement()?;
+        }
+        if let Some(&(_, line)) = self.branches.last() {
+            self.line = line;
+            return Err(self.error("this 'begin' has no matching 'end'"));
+        }
+        if let Some(line) = self.loops.last().map(|repeat| repeat.line) {
+            self.line = line;
+            return Err(self.error("this 'loop' has no matching 'again'"));
+        }
+        if self.has_main {
+            let main = self.labels.contains("main").then(|| self.constants["main"]);
+            let Some(main) = main else {
+                return Err(self.error("the program has no 'main' label"));
+            };
+            self.here = PROGRAM_START;
+            self.emit_op(0x1000 | self.address(main as i64)?)?;
+        }
+        for fixup in core::mem::take(&mut self.fixups) {
+            self.line = fixup.line;
+            if !self.labels.contains(&fixup.name) {
+                return Err(self.error(&format!("undefined name '{}'", fixup.name)));
+            }
+            let value = self.constants[&fixup.name] as i64;
+            self.patch(fixup.address, &fixup.kind, value)?;
+        }
+        Ok(self.rom[PROGRAM_START..self.end.max(PROGRAM_START)].to_vec())
+    }
+
+    fn statement(&mut self) -> Result<(), AssemblyError> {
+        let token = self.next()?;
+        if token.is_string {
+            return Err(self.error(&format!("unexpected string \"{}\"", token.text)));
+        }
+        match token.text.as_str() {
+            ":" => {
+                let name = self.name()?;
+                // a program starting with `main` needs no jump to it
+                if name == "main" && self.here == PROGRAM_START + MAIN_JUMP_SIZE && self.end == 0 {
+                    self.has_main = false;
+                    self.here = PROGRAM_START;
+                }
+                self.define_label(name, self.here)?;
+            }
+            ":next" => {
+                let name = self.name()?;
+                self.define_label(name, self.here + 1)?;
+            }
+            ":unpack" => {
+                let token = self.next()?;
+                let nibble = if token.is("long") {
+                    None
+                } else {
+                    Some(self.nibble(&token)?)
+                };
+                let address = self.here;
+                self.emit_op(0x6000)?;
+                self.emit_op(0x6100)?;
+                self.operand(FixupKind::Unpack(nibble), address)?;
+            }
+            ":breakpoint" | ":proto" => {
+                self.next()?;
+            }
+            ":monitor" => {
+                self.next()?;
+                self.next()?;
+            }
+            ":alias" => {
+                let name = self.name()?;
+                let token = self.next()?;
+                let register = if token.is("{") {
+                    let value = self.block_expression()?;
+                    self.nibble_value(value as i64)?
+                } else {
+                    self.register(&token)?
+                };
+                self.aliases.insert(name, register);
+            }
+            ":const" => {
+                let name = self.name()?;
+                let token = self.next()?;
+                let value = self.constant(&token)?;
+                self.define_constant(name, value)?;
+            }
+            ":org" => {
+                let token = self.next()?;
+                let address = self.constant(&token)? as i64;
+                if !(0..ADDRESS_SPACE as i64).contains(&address) {
+                    return Err(self.error(&format!("address {} is out of range", address)));
+                }
+                self.here = address as usize;
+            }
+            ":macro" => {
+                let name = self.name()?;
+                let mut arguments = Vec::new();
+                loop {
+                    let token = self.next()?;
+                    if token.is("{") {
+                        break;
+                    }
+                    arguments.push(token.text);
+                }
+                let body = self.block()?;
+                self.macros.insert(
+                    name,
+                    Macro {
+                        arguments,
+                        body,
+                        calls: 0,
+                    },
+                );
+            }
+            ":calc" => {
+                let name = self.name()?;
+                self.expect("{")?;
+                let value = self.block_expression()?;
+                self.define_constant(name, value)?;
+            }
+            ":byte" => {
+                let token = self.next()?;
+                let value = if token.is("{") {
+                    self.block_expression()? as i64
+                } else {
+                    self.constant(&token)? as i64
+                };
+                let byte = self.byte(value)?;
+                self.emit_byte(byte)?;
+            }
+            ":pointer" => {
+                let token = self.next()?;
+                let address = self.here;
+                self.emit_op(0)?;
+                if token.is("{") {
+                    let value = self.block_expression()? as i64;
+                    self.patch(address, &FixupKind::Word, value)?;
+                } else {
+                    self.resolve(&token, FixupKind::Word, address)?;
+                }
+            }
+            ":call" => {
+                let address = self.here;
+                self.emit_op(0x2000)?;
+                self.operand(FixupKind::Address, address)?;
+            }
+            ":stringmode" => {
+                let name = self.name()?;
+                let alphabet = self.next()?;
+                if !alphabet.is_string {
+                    return Err(self.error("expected a string of characters"));
+                }
+                self.expect("{")?;
+                let body = self.block()?;
+                let mode = self.string_modes.entry(name).or_default();
+                for (index, character) in alphabet.text.chars().enumerate() {
+                    mode.insert(character, (index, body.clone()));
+                }
+            }
+            ":assert" => {
+                let mut token = self.next()?;
+                let message = if token.is_string {
+                    let message = token.text;
+                    token = self.next()?;
+                    Some(message)
+                } else {
+                    None
+                };
+                if !token.is("{") {
+                    return Err(self.error("expected '{'"));
+                }
+                if self.block_expression()? == 0.0 {
+                    let message = message.unwrap_or_else(|| "assertion failed".to_string());
+                    return Err(self.error(&message));
+                }
+            }
+            ";" | "return" => self.emit_op(0x00ee)?,
+            "clear" => self.emit_op(0x00e0)?,
+            "hires" => self.emit_op(0x00ff)?,
+            "lores" => self.emit_op(0x00fe)?,
+            "scroll-down" => {
+                let n = self.next_nibble()?;
+                self.emit_op(0x00c0 | n as u16)?;
+            }
+            "scroll-up" => {
+                let n = self.next_nibble()?;
+                self.emit_op(0x00d0 | n as u16)?;
+            }
+            "scroll-right" => self.emit_op(0x00fb)?,
+            "scroll-left" => self.emit_op(0x00fc)?,
+            "exit" => self.emit_op(0x00fd)?,
+            "audio" => self.emit_op(0xf002)?,
+            "plane" => {
+                let n = self.next_nibble()?;
+                self.emit_op(0xf001 | (n as u16) << 8)?;
+            }
+            "bcd" => self.register_op(0xf033)?,
+            "saveflags" => self.register_op(0xf075)?,
+            "loadflags" => self.register_op(0xf085)?,
+            "save" => self.save_or_load(0xf055, 0x5002)?,
+            "load" => self.save_or_load(0xf065, 0x5003)?,
+            "sprite" => {
+                let x = self.next_register()?;
+                let y = self.next_register()?;
+                let n = self.next_nibble()?;
+                self.emit_op(0xd000 | (x as u16) << 8 | (y as u16) << 4 | n as u16)?;
+            }
+            "jump" => self.address_op(0x1000)?,
+            "jump0" => self.address_op(0xb000)?,
+            "native" => self.address_op(0x0000)?,
+            "delay" => self.assign_from_register(0xf015)?,
+            "buzzer" => self.assign_from_register(0xf018)?,
+            "pitch" => self.assign_from_register(0xf03a)?,
+            "i" => self.index_op()?,
+            "if" => {
+                let test = self.condition()?;
+                let token = self.next()?;
+                if token.is("then") {
+                    self.skip_when(test, false)?;
+                } else if token.is("begin") {
+                    self.skip_when(test, true)?;
+                    let address = self.here;
+                    self.emit_op(0x1000)?;
+                    self.branches.push((address, token.line));
+                } else {
+                    return Err(self.error("expected 'then' or 'begin'"));
+                }
+            }
+            "else" => {
+                let Some((branch, line)) = self.branches.pop() else {
+                    return Err(self.error("'else' without an 'if ... begin'"));
+                };
+                let address = self.here;
+                self.emit_op(0x1000)?;
+                self.patch(branch, &FixupKind::Address, self.here as i64)?;
+                self.branches.push((address, line));
+            }
+            "end" => {
+                let Some((branch, _)) = self.branches.pop() else {
+                    return Err(self.error("'end' without an 'if ... begin'"));
+                };
+                self.patch(branch, &FixupKind::Address, self.here as i64)?;
+            }
+            "loop" => self.loops.push(Loop {
+                start: self.here,
+                breaks: Vec::new(),
+                line: token.line,
+            }),
+            "while" => {
+                if self.loops.is_empty() {
+                    return Err(self.error("'while' outside of a 'loop'"));
+                }
+                let test = self.condition()?;
+                self.skip_when(test, true)?;
+                let address = self.here;
+                self.emit_op(0x1000)?;
+                if let Some(repeat) = self.loops.last_mut() {
+                    repeat.breaks.push(address);
+                }
+            }
+            "again" => {
+                let Some(repeat) = self.loops.pop() else {
+                    return Err(self.error("'again' without a 'loop'"));
+                };
+                self.emit_op(0x1000 | self.address(repeat.start as i64)?)?;
+                for address in repeat.breaks {
+                    self.patch(address, &FixupKind::Address, self.here as i64)?;
+                }
+            }
+            _ => self.other(token)?,
+        }
+        Ok(())
+    }
+
+    // registers, bytes, macros, string modes and calls, which all start with a name or number
+    fn other(&mut self, token: Token) -> Result<(), AssemblyError> {
+        if let Some(x) = self.as_register(&token.text) {
+            return self.register_assignment(x);
+        }
+        if let Some(number) = parse_number(&token.text) {
+            let byte = self.byte(number as i64)?;
+            return self.emit_byte(byte);
+        }
+        if self.macros.contains_key(&token.text) {
+            return self.expand_macro(&token.text);
+        }
+        if self.string_modes.contains_key(&token.text) {
+            return self.expand_string_mode(&token.text);
+        }
+        if let Some(&value) = self.constants.get(&token.text) {
+            if !self.labels.contains(&token.text) {
+                let byte = self.byte(value as i64)?;
+                return self.emit_byte(byte);
+            }
+        }
+        let address = self.here;
+        self.emit_op(0x2000)?;
+        self.resolve(&token, FixupKind::Address, address)
+    }
+
+    fn register_assignment(&mut self, x: u8) -> Result<(), AssemblyError> {
+        let operator = self.next()?;
+        let x_bits = (x as u16) << 8;
+        match operator.text.as_str() {
+            ":=" => {
+                let token = self.next()?;
+                if token.is("random") {
+                    let token = self.next()?;
+                    let mask = self.byte_operand(&token)?;
+                    self.emit_op(0xc000 | x_bits | mask as u16)
+                } else if token.is("key") {
+                    self.emit_op(0xf00a | x_bits)
+                } else if token.is("delay") {
+                    self.emit_op(0xf007 | x_bits)
+                } else {
+                    match self.operand_value(&token)? {
+                        Operand::Register(y) => self.emit_op(0x8000 | x_bits | (y as u16) << 4),
+                        Operand::Byte(nn) => self.emit_op(0x6000 | x_bits | nn as u16),
+                    }
+                }
+            }
+            "+=" => {
+                let token = self.next()?;
+                match self.operand_value(&token)? {
+                    Operand::Register(y) => self.emit_op(0x8004 | x_bits | (y as u16) << 4),
+                    Operand::Byte(nn) => self.emit_op(0x7000 | x_bits | nn as u16),
+                }
+            }
+            "-=" => {
+                let token = self.next()?;
+                match self.operand_value(&token)? {
+                    Operand::Register(y) => self.emit_op(0x8005 | x_bits | (y as u16) << 4),
+                    Operand::Byte(nn) => self.emit_op(0x7000 | x_bits | nn.wrapping_neg() as u16),
+                }
+            }
+            "|=" | "&=" | "^=" | "=-" | ">>=" | "<<=" => {
+                let operation = match operator.text.as_str() {
+                    "|=" => 0x1,
+                    "&=" => 0x2,
+                    "^=" => 0x3,
+                    "=-" => 0x7,
+                    ">>=" => 0x6,
+                    _ => 0xe,
+                };
+                let y = self.next_register()?;
+                self.emit_op(0x8000 | x_bits | (y as u16) << 4 | operation)
+            }
+            _ => Err(self.error(&format!("unknown operator '{}'", operator.text))),
+        }
+    }
+
+    fn index_op(&mut self) -> Result<(), AssemblyError> {
+        let operator = self.next()?;
+        if operator.is("+=") {
+            return self.register_op(0xf01e);
+        }
+        if !operator.is(":=") {
+            return Err(self.error(&format!("unknown operator '{}'", operator.text)));
+        }
+        let token = self.next()?;
+        if token.is("hex") {
+            self.register_op(0xf029)
+        } else if token.is("bighex") {
+            self.register_op(0xf030)
+        } else if token.is("long") {
+            self.emit_op(0xf000)?;
+            let address = self.here;
+            self.emit_op(0)?;
+            self.operand(FixupKind::Word, address)
+        } else {
+            let address = self.here;
+            self.emit_op(0xa000)?;
+            self.resolve(&token, FixupKind::Address, address)
+        }
+    }
+
+    fn save_or_load(&mut self, single: u16, range: u16) -> Result<(), AssemblyError> {
+        let x = self.next_register()?;
+        if self.peek_is("-") {
+            self.next()?;
+            let y = self.next_register()?;
+            self.emit_op(range | (x as u16) << 8 | (y as u16) << 4)
+        } else {
+            self.emit_op(single | (x as u16) << 8)
+        }
+    }
+
+    fn register_op(&mut self, opcode: u16) -> Result<(), AssemblyError> {
+        let x = self.next_register()?;
+        self.emit_op(opcode | (x as u16) << 8)
+    }
+
+    fn assign_from_register(&mut self, opcode: u16) -> Result<(), AssemblyError> {
+        self.expect(":=")?;
+        self.register_op(opcode)
+    }
+
+    fn address_op(&mut self, opcode: u16) -> Result<(), AssemblyError> {
+        let address = self.here;
+        self.emit_op(opcode)?;
+        self.operand(FixupKind::Address, address)
+    }
+
+    // parses a condition, emitting the instructions comparisons need to leave their result in VF
+    fn condition(&mut self) -> Result<Test, AssemblyError> {
+        let x = self.next_register()?;
+        let operator = self.next()?;
+        let kind = match operator.text.as_str() {
+            "key" => {
+                return Ok(Test {
+                    x,
+                    kind: TestKind::Key,
+                })
+            }
+            "-key" => {
+                return Ok(Test {
+                    x,
+                    kind: TestKind::NotKey,
+                })
+            }
+            "==" | "!=" | "<" | ">" | "<=" | ">=" => operator.text,
+            _ => return Err(self.error(&format!("unknown comparison '{}'", operator.text))),
+        };
+        let token = self.next()?;
+        let operand = self.operand_value(&token)?;
+        let test = match kind.as_str() {
+            "==" => {
+                return Ok(Test {
+                    x,
+                    kind: TestKind::Equal(operand),
+                })
+            }
+            "!=" => {
+                return Ok(Test {
+                    x,
+                    kind: TestKind::NotEqual(operand),
+                })
+            }
+            // VF is 1 when no borrow occurs, so `vf := y; vf -= x` tests y >= x
+            // and `vf := y; vf =- x` tests x >= y
+            ">" | "<=" => 0x5,
+            _ => 0x7,
+        };
+        match operand {
+            Operand::Register(y) => self.emit_op(0x8f00 | (y as u16) << 4)?,
+            Operand::Byte(nn) => self.emit_op(0x6f00 | nn as u16)?,
+        }
+        self.emit_op(0x8f00 | (x as u16) << 4 | test)?;
+        let holds_when_flag_set = matches!(kind.as_str(), "<=" | ">=");
+        let flag = Operand::Byte(holds_when_flag_set as u8);
+        Ok(Test {
+            x: 0xf,
+            kind: TestKind::Equal(flag),
+        })
+    }
+
+    // emits the instruction skipping the next one when the test gives the result asked for
+    fn skip_when(&mut self, test: Test, result: bool) -> Result<(), AssemblyError> {
+        let x_bits = (test.x as u16) << 8;
+        let (kind, result) = match test.kind {
+            TestKind::NotEqual(operand) => (TestKind::Equal(operand), !result),
+            TestKind::NotKey => (TestKind::Key, !result),
+            kind => (kind, result),
+        };
+        let opcode = match (kind, result) {
+            (TestKind::Equal(Operand::Register(y)), true) => 0x5000 | (y as u16) << 4,
+            (TestKind::Equal(Operand::Register(y)), false) => 0x9000 | (y as u16) << 4,
+            (TestKind::Equal(Operand::Byte(nn)), true) => 0x3000 | nn as u16,
+            (TestKind::Equal(Operand::Byte(nn)), false) => 0x4000 | nn as u16,
+            (_, true) => 0xe09e,
+            (_, false) => 0xe0a1,
+        };
+        self.emit_op(opcode | x_bits)
+    }
+
+    fn expand_macro(&mut self, name: &str) -> Result<(), AssemblyError> {
+        let argument_count = self.macros[name].arguments.len();
+        let mut values = Vec::with_capacity(argument_count);
+        for _ in 0..argument_count {
+            values.push(self.next()?);
+        }
+        let line = self.line;
+        let Some(definition) = self.macros.get_mut(name) else {
+            return Ok(());
+        };
+        let calls = definition.calls;
+        definition.calls += 1;
+        let body: Vec<Token> = definition
+            .body
+            .iter()
+            .map(|token| {
+                if token.is("CALLS") {
+                    return number_token(calls as usize, line);
+                }
+                match definition
+                    .arguments
+                    .iter()
+                    .position(|argument| token.is(argument))
+                {
+                    Some(index) => values[index].clone(),
+                    None => token.clone(),
+                }
+            })
+            .collect();
+        self.tokens.splice(self.position..self.position, body);
+        Ok(())
+    }
+
+    fn expand_string_mode(&mut self, name: &str) -> Result<(), AssemblyError> {
+        let text = self.next()?;
+        if !text.is_string {
+            return Err(self.error("expected a string"));
+        }
+        let mut expanded = Vec::new();
+        for (index, character) in text.text.chars().enumerate() {
+            let Some((value, body)) = self.string_modes[name].get(&character) else {
+                return Err(self.error(&format!(
+                    "string mode '{}' has no character '{}'",
+                    name, character
+                )));
+            };
+            for token in body {
+                let replacement = match token.text.as_str() {
+                    _ if token.is_string => None,
+                    "VALUE" => Some(*value),
+                    "CHAR" => Some(character as usize),
+                    "INDEX" => Some(index),
+                    _ => None,
+                };
+                expanded.push(match replacement {
+                    Some(number) => number_token(number, text.line),
+                    None => token.clone(),
+                });
+            }
+        }
+        self.tokens.splice(self.position..self.position, expanded);
+        Ok(())
+    }
+
+    // the tokens up to the `}` closing a block whose `{` was just read
+    fn block(&mut self) -> Result<Vec<Token>, AssemblyError> {
+        let mut depth = 0;
+        let mut tokens = Vec::new();
+        loop {
+            let token = self.next()?;
+            if token.is("{") {
+                depth += 1;
+            } else if token.is("}") {
+                if depth == 0 {
+                    return Ok(tokens);
+                }
+                depth -= 1;
+            }
+            tokens.push(token);
+        }
+    }
+
+    fn block_expression(&mut self) -> Result<f64, AssemblyError> {
+        let tokens = self.block()?;
+        let mut position = 0;
+        let value = self.expression(&tokens, &mut position)?;
+        if position < tokens.len() {
+            return Err(self.error(&format!("unexpected '{}'", tokens[position].text)));
+        }
+        Ok(value)
+    }
+
+    // operators have no precedence and are evaluated from right to left, as in Octo
+    fn expression(&self, tokens: &[Token], position: &mut usize) -> Result<f64, AssemblyError> {
+        let token = tokens
+            .get(*position)
+            .ok_or_else(|| self.error("unexpected end of expression"))?;
+        *position += 1;
+        let text = token.text.as_str();
+        if let Some(operation) = unary_operation(text) {
+            let value = self.expression(tokens, position)?;
+            return operation(value).ok_or_else(|| self.unsupported(text));
+        }
+        let value = if text == "(" {
+            let value = self.expression(tokens, position)?;
+            match tokens.get(*position) {
+                Some(token) if token.is(")") => *position += 1,
+                _ => return Err(self.error("expected ')'")),
+            }
+            value
+        } else if text == "@" {
+            let address = self.expression(tokens, position)? as i64;
+            let address = usize::try_from(address)
+                .ok()
+                .filter(|&address| address < ADDRESS_SPACE)
+                .ok_or_else(|| self.error(&format!("address {} is out of range", address)))?;
+            return Ok(self.rom[address] as f64);
+        } else {
+            self.constant(token)?
+        };
+        match tokens.get(*position) {
+            Some(token) if !token.is(")") => {
+                let text = token.text.as_str();
+                let operation = binary_operation(text)
+                    .ok_or_else(|| self.error(&format!("unknown operator '{}'", text)))?;
+                *position += 1;
+                let rhs = self.expression(tokens, position)?;
+                operation(value, rhs).ok_or_else(|| self.unsupported(text))
+            }
+            _ => Ok(value),
+        }
+    }
+
+    // a number, constant or label already defined, or one of Octo's names for special values
+    fn constant(&self, token: &Token) -> Result<f64, AssemblyError> {
+        if let Some(number) = parse_number(&token.text) {
+            return Ok(number);
+        }
+        match token.text.as_str() {
+            "HERE" => return Ok(self.here as f64),
+            "PI" => return Ok(core::f64::consts::PI),
+            "E" => return Ok(core::f64::consts::E),
+            _ => (),
+        }
+        self.constants
+            .get(&token.text)
+            .copied()
+            .ok_or_else(|| self.error(&format!("undefined name '{}'", token.text)))
+    }
+
+    // reads an address, which may be a label defined further on
+    fn operand(&mut self, kind: FixupKind, address: usize) -> Result<(), AssemblyError> {
+        let token = self.next()?;
+        self.resolve(&token, kind, address)
+    }
+
+    fn resolve(
+        &mut self,
+        token: &Token,
+        kind: FixupKind,
+        address: usize,
+    ) -> Result<(), AssemblyError> {
+        if token.is_string || self.as_register(&token.text).is_some() {
+            return Err(self.error(&format!("expected an address, found '{}'", token.text)));
+        }
+        let is_name = parse_number(&token.text).is_none();
+        if is_name && !self.constants.contains_key(&token.text) {
+            self.fixups.push(Fixup {
+                name: token.text.clone(),
+                address,
+                kind,
+                line: token.line,
+            });
+            return Ok(());
+        }
+        let value = self.constant(token)? as i64;
+        self.patch(address, &kind, value)
+    }
+
+    fn patch(&mut self, address: usize, kind: &FixupKind, value: i64) -> Result<(), AssemblyError> {
+        match *kind {
+            FixupKind::Address => {
+                let nnn = self.address(value)?;
+                self.rom[address] |= (nnn >> 8) as u8;
+                self.rom[address + 1] = nnn as u8;
+            }
+            FixupKind::Word => {
+                let word = self.word(value)?;
+                self.rom[address..address + 2].copy_from_slice(&word.to_be_bytes());
+            }
+            FixupKind::Unpack(nibble) => {
+                let word = self.word(value)?;
+                let [high, low] = word.to_be_bytes();
+                self.rom[address + 1] = match nibble {
+                    Some(nibble) => {
+                        let nnn = self.address(value)?;
+                        nibble << 4 | (nnn >> 8) as u8
+                    }
+                    None => high,
+                };
+                self.rom[address + 3] = low;
+            }
+        }
+        Ok(())
+    }
+
+    fn define_label(&mut self, name: String, address: usize) -> Result<(), AssemblyError> {
+        self.define_constant(name.clone(), address as f64)?;
+        self.labels.insert(name);
+        Ok(())
+    }
+
+    fn define_constant(&mut self, name: String, value: f64) -> Result<(), AssemblyError> {
+        if self.as_register(&name).is_some() || parse_number(&name).is_some() {
+            return Err(self.error(&format!("'{}' can't be used as a name", name)));
+        }
+        if self.labels.contains(&name) {
+            return Err(self.error(&format!("the name '{}' is already defined", name)));
+        }
+        self.constants.insert(name, value);
+        Ok(())
+    }
+
+    fn operand_value(&self, token: &Token) -> Result<Operand, AssemblyError> {
+        match self.as_register(&token.text) {
+            Some(register) if !token.is_string => Ok(Operand::Register(register)),
+            _ => Ok(Operand::Byte(self.byte_operand(token)?)),
+        }
+    }
+
+    fn byte_operand(&self, token: &Token) -> Result<u8, AssemblyError> {
+        let value = self.constant(token)? as i64;
+        self.byte(value)
+    }
+
+    fn as_register(&self, text: &str) -> Option<u8> {
+        if let Some(&register) = self.aliases.get(text) {
+            return Some(register);
+        }
+        let digit = text.strip_prefix(['v', 'V'])?;
+        if digit.len() != 1 {
+            return None;
+        }
+        u8::from_str_radix(digit, 16).ok()
+    }
+
+    fn register(&self, token: &Token) -> Result<u8, AssemblyError> {
+        self.as_register(&token.text)
+            .filter(|_| !token.is_string)
+            .ok_or_else(|| self.error(&format!("expected a register, found '{}'", token.text)))
+    }
+
+    fn next_register(&mut self) -> Result<u8, AssemblyError> {
+        let token = self.next()?;
+        self.register(&token)
+    }
+
+    fn nibble(&self, token: &Token) -> Result<u8, AssemblyError> {
+        let value = self.constant(token)? as i64;
+        self.nibble_value(value)
+    }
+
+    fn next_nibble(&mut self) -> Result<u8, AssemblyError> {
+        let token = self.next()?;
+        self.nibble(&token)
+    }
+
+    fn nibble_value(&self, value: i64) -> Result<u8, AssemblyError> {
+        u8::try_from(value)
+            .ok()
+            .filter(|&nibble| nibble <= 0xf)
+            .ok_or_else(|| self.error(&format!("{} does not fit in a nibble", value)))
+    }
+
+    fn byte(&self, value: i64) -> Result<u8, AssemblyError> {
+        if !(-128..=255).contains(&value) {
+            return Err(self.error(&format!("{} does not fit in a byte", value)));
+        }
+        Ok(value as u8)
+    }
+
+    fn address(&self, value: i64) -> Result<u16, AssemblyError> {
+        u16::try_from(value)
+            .ok()
+            .filter(|&address| address <= 0xfff)
+            .ok_or_else(|| self.error(&format!("address {} is out of range", value)))
+    }
+
+    fn word(&self, value: i64) -> Result<u16, AssemblyError> {
+        u16::try_from(value).map_err(|_| self.error(&format!("{} does not fit in 16 bits", value)))
+    }
+
+    fn emit_op(&mut self, opcode: u16) -> Result<(), AssemblyError> {
+        let [high, low] = opcode.to_be_bytes();
+        self.emit_byte(high)?;
+        self.emit_byte(low)
+    }
+
+    fn emit_byte(&mut self, byte: u8) -> Result<(), AssemblyError> {
+        if !(PROGRAM_START..ADDRESS_SPACE).contains(&self.here) {
+            return Err(self.error(&format!("address {:#06x} is out of range", self.here)));
+        }
+        if self.used[self.here] {
+            return Err(self.error(&format!("data overlaps at address {:#06x}", self.here)));
+        }
+        self.rom[self.here] = byte;
+        self.used[self.here] = true;
+        self.here += 1;
+        self.end = self.end.max(self.here);
+        Ok(())
+    }
+
+    fn name(&mut self) -> Result<String, AssemblyError> {
+        let token = self.next()?;
+        if token.is_string {
+            return Err(self.error("expected a name"));
+        }
+        Ok(token.text)
+    }
+
+    fn expect(&mut self, text: &str) -> Result<(), AssemblyError> {
+        let token = self.next()?;
+        if !token.is(text) {
+            return Err(self.error(&format!("expected '{}', found '{}'", text, token.text)));
+        }
+        Ok(())
+    }
+
+    fn peek_is(&self, text: &str) -> bool {
+        self.tokens
+            .get(self.position)
+            .is_some_and(|token| token.is(text))
+    }
+
+    fn next(&mut self) -> Result<Token, AssemblyError> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .cloned()
+            .ok_or_else(|| self.error("unexpected end of program"))?;
+        self.position += 1;
+        self.line = token.line;
+        Ok(token)
+    }
+
+    fn unsupported(&self, operator: &str) -> AssemblyError {
+        self.error(&format!(
+            "the '{}' operator needs the std feature",
+            operator
+        ))
+    }
+
+    fn error(&self, message: &str) -> AssemblyError {
+        AssemblyError {
+            line: self.line,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for AssemblyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AssemblyError {}
+
+// splits the source on whitespace, keeping quoted strings whole and dropping `#` comments
+fn tokenize(source: &str) -> Result<Vec<Token>, AssemblyError> {
+    let mut tokens = Vec::new();
+    for (index, line) in source.lines().enumerate() {
+        let line_number = index + 1;
+        let mut characters = line.chars().peekable();
+        while let Some(&character) = characters.peek() {
+            if character.is_whitespace() {
+                characters.next();
+            } else if character == '#' {
+                break;
+            } else if character == '"' {
+                characters.next();
+                let mut text = String::new();
+                loop {
+                    let character = characters.next().ok_or(AssemblyError {
+                        line: line_number,
+                        message: "unterminated string".to_string(),
+                    })?;
+                    match character {
+                        '"' => break,
+                        '\\' => text.push(match characters.next() {
+                            Some('n') => '\n',
+                            Some('r') => '\r',
+                            Some('t') => '\t',
+                            Some('v') => '\x0b',
+                            Some('0') => '\0',
+                            Some(escaped) => escaped,
+                            None => '\\',
+                        }),
+                        _ => text.push(character),
+                    }
+                }
+                tokens.push(Token {
+                    text,
+                    is_string: true,
+                    line: line_number,
+                });
+            } else {
+                let mut text = String::new();
+                while let Some(&character) = characters.peek() {
+                    if character.is_whitespace() {
+                        break;
+                    }
+                    text.push(character);
+                    characters.next();
+                }
+                tokens.push(Token {
+                    text,
+                    is_string: false,
+                    line: line_number,
+                });
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+fn number_token(number: usize, line: usize) -> Token {
+    Token {
+        text: number.to_string(),
+        is_string: false,
+        line,
+    }
+}
+
+// decimal, `0x` hexadecimal or `0b` binary, optionally negative
+fn parse_number(text: &str) -> Option<f64> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text),
+    };
+    let value = if let Some(hex) = digits.strip_prefix("0x") {
+        i64::from_str_radix(hex, 16).ok()? as f64
+    } else if let Some(binary) = digits.strip_prefix("0b") {
+        i64::from_str_radix(binary, 2).ok()? as f64
+    } else if digits.starts_with(|character: char| character.is_ascii_digit()) {
+        digits.parse::<f64>().ok()?
+    } else {
+        return None;
+    };
+    Some(if negative { -value } else { value })
+}
+
+type UnaryOperation = fn(f64) -> Option<f64>;
+type BinaryOperation = fn(f64, f64) -> Option<f64>;
+
+fn unary_operation(operator: &str) -> Option<UnaryOperation> {
+    let operation: UnaryOperation = match operator {
+        "-" => |value| Some(-value),
+        "~" => |value| Some(!(value as i64) as f64),
+        "!" => |value| Some((value == 0.0) as u8 as f64),
+        "abs" => |value| Some(if value < 0.0 { -value } else { value }),
+        "sign" => |value| Some((value > 0.0) as u8 as f64 - (value < 0.0) as u8 as f64),
+        "floor" => |value| Some(floor(value)),
+        "ceil" => |value| Some(-floor(-value)),
+        #[cfg(feature = "std")]
+        "sqrt" => |value| Some(value.sqrt()),
+        #[cfg(feature = "std")]
+        "sin" => |value| Some(value.sin()),
+        #[cfg(feature = "std")]
+        "cos" => |value| Some(value.cos()),
+        #[cfg(feature = "std")]
+        "tan" => |value| Some(value.tan()),
+        #[cfg(feature = "std")]
+        "exp" => |value| Some(value.exp()),
+        #[cfg(feature = "std")]
+        "log" => |value| Some(value.ln()),
+        // the transcendental functions come from the standard library
+        #[cfg(not(feature = "std"))]
+        "sqrt" | "sin" | "cos" | "tan" | "exp" | "log" => |_| None,
+        _ => return None,
+    };
+    Some(operation)
+}
+
+fn binary_operation(operator: &str) -> Option<BinaryOperation> {
+    let operation: BinaryOperation = match operator {
+        "+" => |a, b| Some(a + b),
+        "-" => |a, b| Some(a - b),
+        "*" => |a, b| Some(a * b),
+        "/" => |a, b| Some(a / b),
+        "%" => |a, b| Some(a % b),
+        "&" => |a, b| Some((a as i64 & b as i64) as f64),
+        "|" => |a, b| Some((a as i64 | b as i64) as f64),
+        "^" => |a, b| Some((a as i64 ^ b as i64) as f64),
+        "<<" => |a, b| Some((a as i64).wrapping_shl(b as u32) as f64),
+        ">>" => |a, b| Some((a as i64).wrapping_shr(b as u32) as f64),
+        "min" => |a, b| Some(a.min(b)),
+        "max" => |a, b| Some(a.max(b)),
+        "<" => |a, b| Some((a < b) as u8 as f64),
+        "<=" => |a, b| Some((a <= b) as u8 as f64),
+        "==" => |a, b| Some((a == b) as u8 as f64),
+        "!=" => |a, b| Some((a != b) as u8 as f64),
+        ">=" => |a, b| Some((a >= b) as u8 as f64),
+        ">" => |a, b| Some((a > b) as u8 as f64),
+        #[cfg(feature = "std")]
+        "pow" => |a, b| Some(a.powf(b)),
+        #[cfg(not(feature = "std"))]
+        "pow" => |_, _| None,
+        _ => return None,
+    };
+    Some(operation)
+}
+
+fn floor(value: f64) -> f64 {
+    let truncated = value as i64 as f64;
+    if truncated > value {
+        truncated - 1.0
+    } else {
+        truncated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(source: &str) -> String {
+        assemble(source).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn main_first_needs_no_jump() {
+        assert_eq!(
+            assemble(": main v0 := 5 loop again").unwrap(),
+            [0x60, 0x05, 0x12, 0x02]
+        );
+    }
+
+    #[test]
+    fn jumps_to_main_after_other_labels() {
+        assert_eq!(
+            assemble(": f v1 += 1 ;\n: main f jump main").unwrap(),
+            [0x12, 0x06, 0x71, 0x01, 0x00, 0xee, 0x22, 0x02, 0x12, 0x06]
+        );
+    }
+
+    #[test]
+    fn resolves_labels_defined_later() {
+        assert_eq!(
+            assemble(": main i := data ;\n: data 1 2").unwrap(),
+            [0xa2, 0x04, 0x00, 0xee, 0x01, 0x02]
+        );
+    }
+
+    #[test]
+    fn if_then_skips_when_false() {
+        assert_eq!(
+            assemble(": main if v0 == 3 then v1 := 2").unwrap(),
+            [0x40, 0x03, 0x61, 0x02]
+        );
+    }
+
+    #[test]
+    fn if_then_compares_through_vf() {
+        assert_eq!(
+            assemble(": main if v0 > v1 then v2 := 1").unwrap(),
+            [0x8f, 0x10, 0x8f, 0x05, 0x4f, 0x00, 0x62, 0x01]
+        );
+    }
+
+    #[test]
+    fn begin_end() {
+        assert_eq!(
+            assemble(": main if v1 != 7 begin v3 := 1 end").unwrap(),
+            [0x41, 0x07, 0x12, 0x06, 0x63, 0x01]
+        );
+    }
+
+    #[test]
+    fn begin_else_end() {
+        assert_eq!(
+            assemble(": main if v1 == v2 begin v3 := 1 else v3 := 2 end").unwrap(),
+            [0x51, 0x20, 0x12, 0x08, 0x63, 0x01, 0x12, 0x0a, 0x63, 0x02]
+        );
+    }
+
+    #[test]
+    fn loop_while_again() {
+        assert_eq!(
+            assemble(": main loop v0 += 1 while v0 != 10 again").unwrap(),
+            [0x70, 0x01, 0x40, 0x0a, 0x12, 0x08, 0x12, 0x00]
+        );
+    }
+
+    #[test]
+    fn calc_and_const() {
+        assert_eq!(
+            assemble(":calc SIZE { 2 * 7 }\n: main v0 := SIZE").unwrap(),
+            [0x60, 0x0e]
+        );
+        assert_eq!(
+            assemble(":const LIVES 3\n: main v0 := LIVES").unwrap(),
+            [0x60, 0x03]
+        );
+    }
+
+    #[test]
+    fn macros_substitute_arguments() {
+        assert_eq!(
+            assemble(":macro bump REG { REG += 1 }\n: main bump v3 bump v4").unwrap(),
+            [0x73, 0x01, 0x74, 0x01]
+        );
+    }
+
+    #[test]
+    fn byte_data() {
+        assert_eq!(
+            assemble(": main ;\n: data\n:byte 1 :byte { 2 + 3 } 0xFF").unwrap(),
+            [0x00, 0xee, 0x01, 0x05, 0xff]
+        );
+    }
+
+    #[test]
+    fn string_modes() {
+        assert_eq!(
+            assemble(":stringmode text \"ab\" { :byte { CHAR - 96 } }\n: main ; text \"ab\"")
+                .unwrap(),
+            [0x00, 0xee, 0x01, 0x02]
+        );
+    }
+
+    #[test]
+    fn errors_report_their_line() {
+        assert_eq!(
+            error(": main\n  v0 := 1\n  jump nowhere"),
+            "line 3: undefined name 'nowhere'"
+        );
+        assert_eq!(
+            error(": main\n\n  v0 := 300"),
+            "line 3: 300 does not fit in a byte"
+        );
+        assert_eq!(
+            error(": main\n  if v0 == 1 begin\n  v1 := 2"),
+            "line 2: this 'begin' has no matching 'end'"
+        );
+        assert_eq!(
+            error(": main\n: main"),
+            "line 2: the name 'main' is already defined"
+        );
+        assert_eq!(error(": main again"), "line 1: 'again' without a 'loop'");
+        assert_eq!(
+            error(": main v0 := \"unterminated"),
+            "line 1: unterminated string"
+        );
+    }
+}
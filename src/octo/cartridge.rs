@@ -0,0 +1,113 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use serde::Deserialize;
+
+use super::assembler::{self, AssemblyError};
+use super::gif::{self, GifError};
+use super::options::OctoOptions;
+use crate::rom::{Rom, RomError};
+use crate::Chip8;
+
+const PIXELS_PER_BYTE: usize = 4;
+const LENGTH_SIZE: usize = 4;
+
+/// The contents of an Octo cartridge.
+///
+/// Cartridges are GIF images that hide a JSON payload in the lowest two bits of each pixel's
+/// colour index, four pixels to a byte, most significant bits first, across every frame.
+/// The payload starts with its length as a 32 bit big endian number.
+///
+/// Octo stores the program as its source code, which [`Cartridge::rom`] assembles into the
+/// bytes to load; [`Cartridge::load_into`] also applies the options.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cartridge {
+    pub program: String,
+    pub options: OctoOptions,
+}
+
+/// Errors that can occur when reading or loading a cartridge.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CartridgeError {
+    Gif(GifError),
+    UnexpectedEnd,
+    InvalidPayload,
+    Assembly(AssemblyError),
+    Rom(RomError),
+}
+
+#[derive(Deserialize)]
+struct Payload {
+    program: String,
+    #[serde(default)]
+    options: OctoOptions,
+}
+
+impl Cartridge {
+    /// Reads a cartridge from the bytes of its GIF image.
+    pub fn from_gif(bytes: &[u8]) -> Result<Cartridge, CartridgeError> {
+        let frames = gif::decode(bytes).map_err(CartridgeError::Gif)?;
+        let pixels: Vec<u8> = frames
+            .iter()
+            .flat_map(|frame| frame.iter().copied())
+            .collect();
+        let mut bytes = pixels.chunks_exact(PIXELS_PER_BYTE).map(|chunk| {
+            chunk
+                .iter()
+                .fold(0u8, |byte, pixel| (byte << 2) | (pixel & 0b11))
+        });
+
+        let mut length = [0; LENGTH_SIZE];
+        for byte in length.iter_mut() {
+            *byte = bytes.next().ok_or(CartridgeError::UnexpectedEnd)?;
+        }
+        let length = u32::from_be_bytes(length) as usize;
+
+        let json: Vec<u8> = bytes.take(length).collect();
+        if json.len() < length {
+            return Err(CartridgeError::UnexpectedEnd);
+        }
+        let payload: Payload =
+            serde_json::from_slice(&json).map_err(|_| CartridgeError::InvalidPayload)?;
+
+        Ok(Self {
+            program: payload.program,
+            options: payload.options,
+        })
+    }
+
+    /// Assembles the program into a ROM.
+    pub fn rom(&self) -> Result<Rom, CartridgeError> {
+        let bytes = assembler::assemble(&self.program).map_err(CartridgeError::Assembly)?;
+        Rom::new(&bytes).map_err(CartridgeError::Rom)
+    }
+
+    /// Applies the options to the virtual machine and loads the assembled program into it.
+    /// Nothing is changed if the program doesn't fit.
+    pub fn load_into(&self, chip8: &mut Chip8) -> Result<(), CartridgeError> {
+        let rom = self.rom()?;
+        // the program must fit the platform the options switch to, checked up front so that
+        // a failed load leaves the virtual machine as it was
+        let platform = self.options.platform().unwrap_or(chip8.platform());
+        chip8
+            .check_rom_size(&rom, platform)
+            .map_err(CartridgeError::Rom)?;
+        self.options.apply(chip8);
+        chip8.load_rom(&rom).map_err(CartridgeError::Rom)
+    }
+}
+
+impl fmt::Display for CartridgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CartridgeError::Gif(error) => write!(f, "invalid cartridge image: {}", error),
+            CartridgeError::UnexpectedEnd => write!(f, "unexpected end of cartridge data"),
+            CartridgeError::InvalidPayload => write!(f, "invalid cartridge payload"),
+            CartridgeError::Assembly(error) => write!(f, "invalid cartridge program: {}", error),
+            CartridgeError::Rom(error) => write!(f, "invalid cartridge program: {}", error),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CartridgeError {}
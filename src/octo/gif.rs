@@ -0,0 +1,269 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+const MAX_CODE_SIZE: u32 = 12;
+const MAX_CODES: usize = 1 << MAX_CODE_SIZE;
+// the most pixels decoded across every frame, well beyond what a cartridge holding the largest
+// program needs, so that a hostile header can't ask for gigabytes
+const MAX_PIXELS: usize = 1 << 24;
+
+const EXTENSION_INTRODUCER: u8 = 0x21;
+const IMAGE_SEPARATOR: u8 = 0x2c;
+const TRAILER: u8 = 0x3b;
+
+/// Errors that can occur when decoding a GIF.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GifError {
+    InvalidHeader,
+    UnexpectedEnd,
+    InvalidBlock,
+    InvalidImageData,
+    TooLarge,
+}
+
+/// Decodes the frames of a GIF into the colour index of each of their pixels.
+/// Colour tables are skipped. Fails if the frames add up to more than 16 million pixels.
+pub fn decode(bytes: &[u8]) -> Result<Vec<Vec<u8>>, GifError> {
+    let mut reader = Reader { bytes, position: 0 };
+
+    let signature = reader.take(6)?;
+    if signature != b"GIF87a" && signature != b"GIF89a" {
+        return Err(GifError::InvalidHeader);
+    }
+    // logical screen descriptor: width, height, flags, background colour and aspect ratio
+    let descriptor = reader.take(7)?;
+    skip_color_table(&mut reader, descriptor[4])?;
+
+    let mut frames = Vec::new();
+    let mut remaining_pixels = MAX_PIXELS;
+    loop {
+        match reader.byte()? {
+            EXTENSION_INTRODUCER => {
+                reader.byte()?;
+                read_sub_blocks(&mut reader)?;
+            }
+            IMAGE_SEPARATOR => frames.push(read_frame(&mut reader, &mut remaining_pixels)?),
+            TRAILER => return Ok(frames),
+            _ => return Err(GifError::InvalidBlock),
+        }
+    }
+}
+
+fn read_frame(reader: &mut Reader, remaining_pixels: &mut usize) -> Result<Vec<u8>, GifError> {
+    // image descriptor: left, top, width, height and flags
+    let descriptor = reader.take(9)?;
+    let width = u16::from_le_bytes([descriptor[4], descriptor[5]]) as usize;
+    let height = u16::from_le_bytes([descriptor[6], descriptor[7]]) as usize;
+    let flags = descriptor[8];
+    let pixel_count = width * height;
+    *remaining_pixels = remaining_pixels
+        .checked_sub(pixel_count)
+        .ok_or(GifError::TooLarge)?;
+    let is_interlaced = flags & 0x40 != 0;
+    skip_color_table(reader, flags)?;
+
+    let min_code_size = reader.byte()?;
+    let data = read_sub_blocks(reader)?;
+    let mut pixels = decompress(min_code_size, &data, pixel_count)?;
+    pixels.resize(pixel_count, 0);
+    if is_interlaced {
+        pixels = deinterlace(&pixels, width, height);
+    }
+    Ok(pixels)
+}
+
+fn skip_color_table(reader: &mut Reader, flags: u8) -> Result<(), GifError> {
+    if flags & 0x80 != 0 {
+        let size = 3 * (2 << (flags & 0x07));
+        reader.take(size)?;
+    }
+    Ok(())
+}
+
+fn read_sub_blocks(reader: &mut Reader) -> Result<Vec<u8>, GifError> {
+    let mut data = Vec::new();
+    loop {
+        let size = reader.byte()? as usize;
+        if size == 0 {
+            return Ok(data);
+        }
+        data.extend_from_slice(reader.take(size)?);
+    }
+}
+
+// the rows of an interlaced image are stored in four passes:
+// every 8th row from 0, every 8th row from 4, every 4th row from 2 and every 2nd row from 1
+fn deinterlace(pixels: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let mut output = vec![0; pixels.len()];
+    let passes = [(0, 8), (4, 8), (2, 4), (1, 2)];
+    let rows = passes
+        .iter()
+        .flat_map(|&(start, step)| (start..height).step_by(step));
+    for (source, destination) in rows.enumerate() {
+        let source = &pixels[source * width..(source + 1) * width];
+        output[destination * width..(destination + 1) * width].copy_from_slice(source);
+    }
+    output
+}
+
+// stops once the given number of pixels is decoded, which bounds the output
+fn decompress(min_code_size: u8, data: &[u8], pixel_count: usize) -> Result<Vec<u8>, GifError> {
+    if !(1..MAX_CODE_SIZE as u8).contains(&min_code_size) {
+        return Err(GifError::InvalidImageData);
+    }
+    let clear_code = 1 << min_code_size;
+    let end_code = clear_code + 1;
+
+    // each code is a previous code (its prefix) followed by one more index
+    let mut prefixes = vec![0u16; MAX_CODES];
+    let mut suffixes = vec![0u8; MAX_CODES];
+    let mut firsts = vec![0u8; MAX_CODES];
+    let mut lengths = vec![0usize; MAX_CODES];
+    for code in 0..clear_code {
+        suffixes[code] = code as u8;
+        firsts[code] = code as u8;
+        lengths[code] = 1;
+    }
+
+    let mut output = Vec::with_capacity(pixel_count);
+    let mut bits = BitReader::new(data);
+    let mut code_size = min_code_size as u32 + 1;
+    let mut next_code = end_code + 1;
+    let mut previous: Option<usize> = None;
+
+    while output.len() < pixel_count {
+        let code = match bits.read(code_size) {
+            Some(code) => code as usize,
+            None => break,
+        };
+        if code == clear_code {
+            code_size = min_code_size as u32 + 1;
+            next_code = end_code + 1;
+            previous = None;
+            continue;
+        }
+        if code == end_code {
+            break;
+        }
+
+        let previous_code = match previous {
+            Some(previous_code) => previous_code,
+            None => {
+                if code >= clear_code {
+                    return Err(GifError::InvalidImageData);
+                }
+                output.push(code as u8);
+                previous = Some(code);
+                continue;
+            }
+        };
+
+        let first = if code < next_code {
+            write_code(&mut output, code, &prefixes, &suffixes, &lengths);
+            firsts[code]
+        } else if code == next_code {
+            let first = firsts[previous_code];
+            write_code(&mut output, previous_code, &prefixes, &suffixes, &lengths);
+            output.push(first);
+            first
+        } else {
+            return Err(GifError::InvalidImageData);
+        };
+
+        if next_code < MAX_CODES {
+            prefixes[next_code] = previous_code as u16;
+            suffixes[next_code] = first;
+            firsts[next_code] = firsts[previous_code];
+            lengths[next_code] = lengths[previous_code] + 1;
+            next_code += 1;
+            if next_code == 1 << code_size && code_size < MAX_CODE_SIZE {
+                code_size += 1;
+            }
+        }
+        previous = Some(code);
+    }
+    output.truncate(pixel_count);
+    Ok(output)
+}
+
+// writes the indices of a code, walking back through its prefixes from the end
+fn write_code(
+    output: &mut Vec<u8>,
+    code: usize,
+    prefixes: &[u16],
+    suffixes: &[u8],
+    lengths: &[usize],
+) {
+    let start = output.len();
+    output.resize(start + lengths[code], 0);
+    let mut code = code;
+    for index in (start..output.len()).rev() {
+        output[index] = suffixes[code];
+        code = prefixes[code] as usize;
+    }
+}
+
+impl fmt::Display for GifError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GifError::InvalidHeader => write!(f, "invalid gif header"),
+            GifError::UnexpectedEnd => write!(f, "unexpected end of gif data"),
+            GifError::InvalidBlock => write!(f, "invalid gif block"),
+            GifError::InvalidImageData => write!(f, "invalid gif image data"),
+            GifError::TooLarge => write!(f, "gif images too large"),
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn byte(&mut self) -> Result<u8, GifError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn take(&mut self, size: usize) -> Result<&'a [u8], GifError> {
+        let bytes = self
+            .bytes
+            .get(self.position..self.position + size)
+            .ok_or(GifError::UnexpectedEnd)?;
+        self.position += size;
+        Ok(bytes)
+    }
+}
+
+// codes are packed least significant bit first
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    buffer: u32,
+    buffered_bits: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            position: 0,
+            buffer: 0,
+            buffered_bits: 0,
+        }
+    }
+
+    fn read(&mut self, size: u32) -> Option<u32> {
+        while self.buffered_bits < size {
+            let byte = *self.bytes.get(self.position)?;
+            self.position += 1;
+            self.buffer |= (byte as u32) << self.buffered_bits;
+            self.buffered_bits += 8;
+        }
+        let value = self.buffer & ((1 << size) - 1);
+        self.buffer >>= size;
+        self.buffered_bits -= size;
+        Some(value)
+    }
+}
@@ -0,0 +1,11 @@
+//! Support for programs published with Octo, the CHIP-8 IDE.
+
+pub use assembler::{assemble, AssemblyError};
+pub use cartridge::{Cartridge, CartridgeError};
+pub use gif::GifError;
+pub use options::{OctoOptions, OctoOptionsError};
+
+mod assembler;
+mod cartridge;
+mod gif;
+mod options;
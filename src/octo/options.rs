@@ -0,0 +1,232 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::fmt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::font::{Font, FontStyle};
+use crate::frame::Color;
+use crate::platform::Platform;
+use crate::quirks::Quirks;
+use crate::speed::{MAX_SPEED, MIN_SPEED};
+use crate::Chip8;
+
+const FRAMES_PER_SECOND: u32 = 60;
+const FONT_STYLES: [(&str, FontStyle); 5] = [
+    ("octo", FontStyle::Octo),
+    ("vip", FontStyle::Vip),
+    ("dream6800", FontStyle::Dream6800),
+    ("eti660", FontStyle::Eti660),
+    ("schip", FontStyle::Schip),
+];
+// the memory sizes Octo offers, which stand in for the platform; 65024 is XO-CHIP's
+const MAX_SIZES: [(u32, Platform); 3] = [
+    (3216, Platform::Vip),
+    (3583, Platform::Schip),
+    (3584, Platform::Modern),
+];
+
+/// The per-program settings Octo stores alongside a program.
+/// Settings the virtual machine has no equivalent for are kept, as are settings unknown to
+/// this crate, so they all survive a round trip.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OctoOptions {
+    /// Instructions per frame, at 60 frames per second.
+    pub tickrate: u32,
+    pub fill_color: String,
+    pub fill_color2: String,
+    pub blend_color: String,
+    pub background_color: String,
+    pub buzz_color: String,
+    pub quiet_color: String,
+    pub shift_quirks: bool,
+    pub load_store_quirks: bool,
+    pub vf_order_quirks: bool,
+    pub clip_quirks: bool,
+    pub v_blank_quirks: bool,
+    pub jump_quirks: bool,
+    pub logic_quirks: bool,
+    #[serde(rename = "enableXO")]
+    pub enable_xo: bool,
+    pub screen_rotation: u32,
+    pub max_size: u32,
+    pub touch_input_mode: String,
+    pub font_style: String,
+    /// Settings unknown to this crate, written back as they were read.
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+/// Error returned when Octo options cannot be parsed from JSON.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OctoOptionsError {
+    message: String,
+}
+
+impl OctoOptions {
+    /// Parses options from Octo's JSON form. Missing settings take Octo's defaults.
+    pub fn from_json(json: &str) -> Result<OctoOptions, OctoOptionsError> {
+        serde_json::from_str(json).map_err(|error| OctoOptionsError {
+            message: error.to_string(),
+        })
+    }
+
+    /// Serializes the options into Octo's JSON form.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("options are always serializable")
+    }
+
+    /// Creates options from the speed, quirks, colours, font and platform of the virtual machine,
+    /// with Octo's defaults for everything else.
+    pub fn from_chip8(chip8: &Chip8) -> OctoOptions {
+        let quirks = chip8.quirks();
+        let (on_color, off_color) = chip8.colors();
+        OctoOptions {
+            tickrate: chip8.speed() / FRAMES_PER_SECOND,
+            fill_color: format_color(on_color),
+            background_color: format_color(off_color),
+            shift_quirks: quirks.shift,
+            load_store_quirks: quirks.load_store,
+            clip_quirks: quirks.clip,
+            v_blank_quirks: quirks.vblank,
+            jump_quirks: quirks.jump,
+            logic_quirks: quirks.logic,
+            max_size: format_max_size(chip8.platform()),
+            font_style: format_font_style(chip8.font()),
+            ..OctoOptions::default()
+        }
+    }
+
+    /// Returns the platform the options ask for. Octo only tells us the memory size, so this is
+    /// the platform with that size in Octo, if it is one the virtual machine follows.
+    pub fn platform(&self) -> Option<Platform> {
+        MAX_SIZES
+            .iter()
+            .find(|&&(size, _)| size == self.max_size)
+            .map(|&(_, platform)| platform)
+    }
+
+    /// Returns the speed the tick rate asks for, if the virtual machine can run at it.
+    pub fn speed(&self) -> Option<u32> {
+        self.tickrate
+            .checked_mul(FRAMES_PER_SECOND)
+            .filter(|speed| (MIN_SPEED..=MAX_SPEED).contains(speed))
+    }
+
+    /// Returns the quirks the options ask for.
+    pub fn quirks(&self) -> Quirks {
+        Quirks {
+            shift: self.shift_quirks,
+            load_store: self.load_store_quirks,
+            clip: self.clip_quirks,
+            vblank: self.v_blank_quirks,
+            jump: self.jump_quirks,
+            logic: self.logic_quirks,
+        }
+    }
+
+    /// Returns the colours of pixels that are on and off, if both colours are valid and differ.
+    pub fn colors(&self) -> Option<(Color, Color)> {
+        let on_color = parse_color(&self.fill_color)?;
+        let off_color = parse_color(&self.background_color)?;
+        (on_color != off_color).then_some((on_color, off_color))
+    }
+
+    /// Returns the font the options ask for, if it is one the virtual machine ships.
+    pub fn font_style(&self) -> Option<FontStyle> {
+        FONT_STYLES
+            .iter()
+            .find(|(name, _)| *name == self.font_style)
+            .map(|&(_, style)| style)
+    }
+
+    /// Applies the speed, quirks, colours, font and platform to the virtual machine.
+    /// Settings it can't follow, such as a font that doesn't fit at the font address of the
+    /// memory layout, are skipped.
+    pub fn apply(&self, chip8: &mut Chip8) {
+        if let Some(platform) = self.platform() {
+            chip8.set_platform(platform);
+        }
+        if let Some(speed) = self.speed() {
+            chip8.set_speed(speed);
+        }
+        chip8.set_quirks(self.quirks());
+        if let Some((on_color, off_color)) = self.colors() {
+            chip8.set_colors(on_color, off_color);
+        }
+        if let Some(style) = self.font_style() {
+            let _ = chip8.set_font_style(style);
+        }
+    }
+}
+
+/// The defaults are those of Octo itself.
+impl Default for OctoOptions {
+    fn default() -> Self {
+        Self {
+            tickrate: 20,
+            fill_color: "#FFCC00".to_string(),
+            fill_color2: "#FF6600".to_string(),
+            blend_color: "#662200".to_string(),
+            background_color: "#996600".to_string(),
+            buzz_color: "#FFAA00".to_string(),
+            quiet_color: "#000000".to_string(),
+            shift_quirks: false,
+            load_store_quirks: false,
+            vf_order_quirks: false,
+            clip_quirks: false,
+            v_blank_quirks: false,
+            jump_quirks: false,
+            logic_quirks: false,
+            enable_xo: false,
+            screen_rotation: 0,
+            max_size: 3584,
+            touch_input_mode: "none".to_string(),
+            font_style: "octo".to_string(),
+            extra: BTreeMap::new(),
+        }
+    }
+}
+
+impl fmt::Display for OctoOptionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid octo options: {}", self.message)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for OctoOptionsError {}
+
+// custom fonts have no name in Octo, so they are stored as its default
+fn format_font_style(font: Font) -> String {
+    let (name, _) = FONT_STYLES
+        .iter()
+        .find(|&&(_, style)| Font::from(style) == font)
+        .unwrap_or(&FONT_STYLES[0]);
+    name.to_string()
+}
+
+fn format_max_size(platform: Platform) -> u32 {
+    let (size, _) = MAX_SIZES
+        .iter()
+        .find(|&&(_, size_platform)| size_platform == platform)
+        .expect("every platform has a size");
+    *size
+}
+
+fn format_color(color: Color) -> String {
+    format!("#{:02X}{:02X}{:02X}", color[0], color[1], color[2])
+}
+
+// parses a `#RRGGBB` colour
+fn parse_color(color: &str) -> Option<Color> {
+    let hex = color.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    let [_, red, green, blue] = value.to_be_bytes();
+    Some([red, green, blue, u8::MAX])
+}
@@ -0,0 +1,46 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::layout::{DEFAULT_PROGRAM_START, MEMORY_SIZE};
+
+const PROGRAM_START: usize = DEFAULT_PROGRAM_START as usize;
+// the VIP interpreter keeps its stack, work area and display buffer in the top 352 bytes
+const VIP_RESERVED_SIZE: usize = 0x160;
+
+/// Enum representing the Chip-8 platform whose limits the virtual machine follows.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Platform {
+    /// The original interpreter on the COSMAC VIP.
+    Vip,
+    /// The behaviour most modern interpreters settled on.
+    #[default]
+    Modern,
+    /// SUPER-CHIP on the HP 48 calculators. `FX30` is only an instruction here, and needs
+    /// a font with big glyphs, such as `FontStyle::Schip`, to point at them.
+    Schip,
+}
+
+impl Platform {
+    /// Returns the largest program, in bytes, the platform can load at the default program start.
+    pub fn max_program_size(&self) -> usize {
+        match self {
+            Platform::Vip => MEMORY_SIZE - PROGRAM_START - VIP_RESERVED_SIZE,
+            Platform::Modern | Platform::Schip => MEMORY_SIZE - PROGRAM_START,
+        }
+    }
+
+    /// Returns how many subroutine calls the platform can nest, or `None` when only the
+    /// stack pointer limits them.
+    pub fn stack_depth(&self) -> Option<u8> {
+        match self {
+            Platform::Vip => Some(12),
+            Platform::Modern => None,
+            Platform::Schip => Some(16),
+        }
+    }
+}
@@ -0,0 +1,165 @@
+//! Python bindings for the virtual machine, behind the `python` feature.
+//!
+//! Built with maturin (see `pyproject.toml`), this gives a `chip8_core` module with a `Chip8` class.
+//! Frames and memory are returned as `bytes`, which NumPy can view without copying:
+//!
+//! ```python
+//! frame = numpy.frombuffer(chip8.frame(), dtype=numpy.uint8).reshape(32, 64, 4)
+//! ```
+
+// the pymethods expansion converts every PyResult error, which clippy flags
+#![allow(clippy::useless_conversion)]
+
+use alloc::string::ToString;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+
+use crate::frame::{FRAME_HEIGHT, FRAME_WIDTH};
+use crate::keypad::{Key, KeyState};
+use crate::rom::Rom;
+use crate::speed::{MAX_SPEED, MIN_SPEED};
+use crate::Chip8;
+
+const FRAME_MICRO_SECONDS: u32 = 1_000_000 / 60;
+
+/// The Chip-8 virtual machine.
+#[pyclass(name = "Chip8", module = "chip8_core")]
+pub struct PyChip8 {
+    chip8: Chip8,
+}
+
+#[pymethods]
+impl PyChip8 {
+    /// The width of the frame in pixels.
+    #[classattr]
+    const FRAME_WIDTH: usize = FRAME_WIDTH;
+
+    /// The height of the frame in pixels.
+    #[classattr]
+    const FRAME_HEIGHT: usize = FRAME_HEIGHT;
+
+    #[new]
+    #[pyo3(signature = (seed = 0))]
+    fn new(seed: u32) -> Self {
+        Self {
+            chip8: Chip8::new(seed),
+        }
+    }
+
+    /// Validates and loads a program, after clearing any previous program.
+    /// Raises `ValueError` if the program is empty or too large.
+    fn load(&mut self, program: &[u8]) -> PyResult<()> {
+        let rom = Rom::new(program).map_err(|error| PyValueError::new_err(error.to_string()))?;
+        self.chip8
+            .load_rom(&rom)
+            .map_err(|error| PyValueError::new_err(error.to_string()))
+    }
+
+    /// Resets the virtual machine, clearing the loaded program.
+    fn reset(&mut self) {
+        self.chip8.reset();
+    }
+
+    /// Executes a single cycle.
+    fn step(&mut self) {
+        self.chip8.step();
+    }
+
+    /// Progresses the virtual machine by the given time delta in microseconds.
+    fn update(&mut self, time_delta: u32) {
+        self.chip8.update(time_delta);
+    }
+
+    /// Progresses the virtual machine by one frame, a 60th of a second.
+    fn run_frame(&mut self) {
+        self.chip8.update(FRAME_MICRO_SECONDS);
+    }
+
+    /// Presses a key, from 0x0 to 0xF.
+    fn press_key(&mut self, key: u8) -> PyResult<()> {
+        self.chip8.handle_key_event(to_key(key)?, KeyState::Pressed);
+        Ok(())
+    }
+
+    /// Releases a key, from 0x0 to 0xF.
+    fn release_key(&mut self, key: u8) -> PyResult<()> {
+        self.chip8
+            .handle_key_event(to_key(key)?, KeyState::Released);
+        Ok(())
+    }
+
+    /// Returns the frame as RGBA bytes, row by row.
+    fn frame<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new_bound(py, &self.chip8.cpu.frame.buffer)
+    }
+
+    /// Returns the frame as one byte per pixel, row by row, 1 if the pixel is on and 0 if not.
+    fn pixels<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        let frame = &self.chip8.cpu.frame;
+        let pixels: alloc::vec::Vec<u8> = (0..FRAME_HEIGHT)
+            .flat_map(|y| (0..FRAME_WIDTH).map(move |x| frame.is_pixel_on(x, y) as u8))
+            .collect();
+        PyBytes::new_bound(py, &pixels)
+    }
+
+    /// Returns the registers as a dict of `pc`, `i`, `sp`, `dt`, `st` and the list `v`.
+    fn registers<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let registers = self.chip8.registers();
+        let dict = PyDict::new_bound(py);
+        dict.set_item("pc", registers.pc)?;
+        dict.set_item("i", registers.i)?;
+        dict.set_item("sp", registers.sp)?;
+        dict.set_item("dt", registers.dt)?;
+        dict.set_item("st", registers.st)?;
+        dict.set_item("v", registers.v.to_vec())?;
+        Ok(dict)
+    }
+
+    /// Returns a copy of memory.
+    fn memory<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new_bound(py, self.chip8.cpu.ram.data())
+    }
+
+    /// Saves the state of the virtual machine, so that it can be resumed with `load_state`.
+    fn save_state<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new_bound(py, &self.chip8.save_state())
+    }
+
+    /// Restores a state saved with `save_state`.
+    /// Raises `ValueError`, leaving the virtual machine untouched, if the state is invalid.
+    fn load_state(&mut self, state: &[u8]) -> PyResult<()> {
+        self.chip8
+            .load_state(state)
+            .map_err(|error| PyValueError::new_err(error.to_string()))
+    }
+
+    /// The speed in instructions per second, from `MIN_SPEED` to `MAX_SPEED`.
+    /// Setting a speed outside that range raises `ValueError`.
+    #[getter]
+    fn speed(&self) -> u32 {
+        self.chip8.speed()
+    }
+
+    #[setter]
+    fn set_speed(&mut self, instructions_per_second: u32) -> PyResult<()> {
+        self.chip8
+            .try_set_speed(instructions_per_second)
+            .map_err(|error| PyValueError::new_err(error.to_string()))
+    }
+}
+
+fn to_key(key: u8) -> PyResult<Key> {
+    if key > 0xF {
+        return Err(PyValueError::new_err("key must be from 0x0 to 0xF"));
+    }
+    Ok(key.into())
+}
+
+#[pymodule]
+fn chip8_core(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyChip8>()?;
+    module.add("MIN_SPEED", MIN_SPEED)?;
+    module.add("MAX_SPEED", MAX_SPEED)?;
+    Ok(())
+}
@@ -0,0 +1,84 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+
+#[cfg(feature = "wasm")]
+use crate::alloc::string::ToString;
+
+use crate::platform::Platform;
+
+/// The behaviours that differ between Chip-8 interpreters, and that programs may depend on.
+/// The default matches the behaviour of most modern interpreters.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quirks {
+    /// `8XY6` and `8XYE` shift VX in place, rather than shifting VY into VX.
+    pub shift: bool,
+    /// `FX55` and `FX65` leave I unchanged, rather than incrementing it past the last register.
+    pub load_store: bool,
+    /// Sprites are clipped at the edges of the screen, rather than wrapping around.
+    pub clip: bool,
+    /// `DXYN` waits for the display to refresh before drawing.
+    pub vblank: bool,
+    /// `BNNN` jumps to NNN plus VX, where X is the highest nibble of NNN, rather than NNN plus V0.
+    pub jump: bool,
+    /// `8XY1`, `8XY2` and `8XY3` reset VF to zero.
+    pub logic: bool,
+}
+
+impl Quirks {
+    /// The behaviour of the original interpreter on the COSMAC VIP.
+    pub const fn vip() -> Self {
+        Self {
+            shift: false,
+            load_store: false,
+            clip: true,
+            vblank: true,
+            jump: false,
+            logic: true,
+        }
+    }
+
+    /// The behaviour most modern interpreters settled on.
+    pub const fn modern() -> Self {
+        Self {
+            shift: false,
+            load_store: true,
+            clip: true,
+            vblank: false,
+            jump: false,
+            logic: false,
+        }
+    }
+
+    /// The behaviour of SUPER-CHIP on the HP 48 calculators.
+    pub const fn schip() -> Self {
+        Self {
+            shift: true,
+            load_store: true,
+            clip: true,
+            vblank: false,
+            jump: true,
+            logic: false,
+        }
+    }
+
+    /// Returns the behaviour of the given platform.
+    pub const fn for_platform(platform: Platform) -> Self {
+        match platform {
+            Platform::Vip => Self::vip(),
+            Platform::Modern => Self::modern(),
+            Platform::Schip => Self::schip(),
+        }
+    }
+}
+
+impl Default for Quirks {
+    fn default() -> Self {
+        Self::modern()
+    }
+}
@@ -0,0 +1,99 @@
+use alloc::vec::Vec;
+use nanorand::{Rng, WyRand};
+
+// WyRand advances its state by this constant every time a number is drawn,
+// nanorand doesn't expose the state so we track it alongside the generator
+const WYRAND_INCREMENT: u64 = 0xa076_1d64_78bd_642f;
+
+/// A source of random bytes for the `CXNN` instruction.
+///
+/// Implement this to plug in a hardware generator, a scripted sequence for tests or a generator
+/// that mimics a particular interpreter. Sources are `Send`, so that a virtual machine can be
+/// handed to another thread.
+pub trait RandomSource: Send {
+    /// Returns the next random byte.
+    fn next_byte(&mut self) -> u8;
+
+    /// Reseeds the source.
+    fn reseed(&mut self, seed: u64);
+
+    /// Returns the current state of the source, which can be restored with `set_state`.
+    fn state(&self) -> u64;
+
+    /// Restores a state previously returned by `state`.
+    fn set_state(&mut self, state: u64);
+}
+
+/// The default random source, a seeded WyRand generator.
+#[derive(Clone)]
+pub struct WyRandSource {
+    rng: WyRand,
+    state: u64,
+}
+
+impl WyRandSource {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: WyRand::new_seed(seed),
+            state: seed,
+        }
+    }
+}
+
+impl RandomSource for WyRandSource {
+    fn next_byte(&mut self) -> u8 {
+        self.state = self.state.wrapping_add(WYRAND_INCREMENT);
+        self.rng.generate::<u8>()
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        *self = Self::new(seed);
+    }
+
+    fn state(&self) -> u64 {
+        self.state
+    }
+
+    fn set_state(&mut self, state: u64) {
+        *self = Self::new(state);
+    }
+}
+
+/// A random source that plays back a fixed sequence of bytes, wrapping around at the end.
+/// The state is the position in the sequence, and reseeding moves to that position.
+#[derive(Clone)]
+pub struct SequenceSource {
+    bytes: Vec<u8>,
+    position: usize,
+}
+
+impl SequenceSource {
+    /// Creates a source from the given bytes, which must not be empty.
+    pub fn new(bytes: &[u8]) -> Self {
+        assert!(!bytes.is_empty(), "sequence must not be empty");
+        Self {
+            bytes: bytes.to_vec(),
+            position: 0,
+        }
+    }
+}
+
+impl RandomSource for SequenceSource {
+    fn next_byte(&mut self) -> u8 {
+        let byte = self.bytes[self.position];
+        self.position = (self.position + 1) % self.bytes.len();
+        byte
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.set_state(seed);
+    }
+
+    fn state(&self) -> u64 {
+        self.position as u64
+    }
+
+    fn set_state(&mut self, state: u64) {
+        self.position = (state % self.bytes.len() as u64) as usize;
+    }
+}
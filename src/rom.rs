@@ -0,0 +1,151 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use sha1_smol::Sha1;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::platform::Platform;
+
+/// A validated Chip-8 program, along with some facts about it.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rom {
+    bytes: Vec<u8>,
+    sha1: [u8; 20],
+    crc32: u32,
+    uses_schip_opcodes: bool,
+    uses_xo_opcodes: bool,
+}
+
+/// Errors that can occur when validating a program.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RomError {
+    Empty,
+    TooLarge { size: usize, max_size: usize },
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Rom {
+    /// Creates a program from its bytes.
+    /// The program must not be empty and must fit into memory on at least one platform;
+    /// whether it fits the active platform is checked when it is loaded.
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(bytes: &[u8]) -> Result<Rom, RomError> {
+        let max_size = Platform::Modern.max_program_size();
+        if bytes.is_empty() {
+            return Err(RomError::Empty);
+        }
+        if bytes.len() > max_size {
+            return Err(RomError::TooLarge {
+                size: bytes.len(),
+                max_size,
+            });
+        }
+
+        let (uses_schip_opcodes, uses_xo_opcodes) = scan_opcodes(bytes);
+        Ok(Self {
+            bytes: bytes.to_vec(),
+            sha1: Sha1::from(bytes).digest().bytes(),
+            crc32: crc32fast::hash(bytes),
+            uses_schip_opcodes,
+            uses_xo_opcodes,
+        })
+    }
+
+    /// Returns the size of the program in bytes.
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Always false, since empty programs are rejected.
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Returns the SHA-1 hash of the program as a lowercase hex string.
+    pub fn sha1_hex(&self) -> String {
+        self.sha1
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    /// Returns the CRC32 checksum of the program.
+    pub fn crc32(&self) -> u32 {
+        self.crc32
+    }
+
+    /// Returns whether the program appears to use SUPER-CHIP instructions.
+    /// This is a heuristic, since data can look like instructions.
+    pub fn uses_schip_opcodes(&self) -> bool {
+        self.uses_schip_opcodes
+    }
+
+    /// Returns whether the program appears to use XO-CHIP instructions.
+    /// This is a heuristic, since data can look like instructions.
+    pub fn uses_xo_opcodes(&self) -> bool {
+        self.uses_xo_opcodes
+    }
+
+    /// Returns whether the program fits into memory on the given platform.
+    pub fn fits(&self, platform: Platform) -> bool {
+        self.len() <= platform.max_program_size()
+    }
+}
+
+impl Rom {
+    /// Returns the bytes of the program.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the SHA-1 hash of the program.
+    pub fn sha1(&self) -> [u8; 20] {
+        self.sha1
+    }
+}
+
+impl fmt::Display for RomError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RomError::Empty => write!(f, "program is empty"),
+            RomError::TooLarge { size, max_size } => write!(
+                f,
+                "program is {} bytes but at most {} bytes can be loaded",
+                size, max_size
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RomError {}
+
+// scans every instruction aligned word for opcodes that only exist in the extensions
+fn scan_opcodes(bytes: &[u8]) -> (bool, bool) {
+    let mut uses_schip_opcodes = false;
+    let mut uses_xo_opcodes = false;
+
+    for word in bytes.chunks_exact(2) {
+        let opcode = u16::from_be_bytes([word[0], word[1]]);
+        let op_type = (opcode & 0xf000) >> 12;
+        let x = (opcode & 0x0f00) >> 8;
+        let nn = opcode & 0x00ff;
+        let n = opcode & 0x000f;
+
+        match (op_type, x, nn) {
+            (0x0, 0x0, 0xc1..=0xcf | 0xfb..=0xff) => uses_schip_opcodes = true,
+            (0xd, _, _) if n == 0 => uses_schip_opcodes = true,
+            (0xf, _, 0x30 | 0x75 | 0x85) => uses_schip_opcodes = true,
+            (0x0, 0x0, 0xd1..=0xdf) => uses_xo_opcodes = true,
+            (0x5, _, _) if n == 0x2 || n == 0x3 => uses_xo_opcodes = true,
+            (0xf, 0x0, 0x00 | 0x02) => uses_xo_opcodes = true,
+            (0xf, _, 0x01 | 0x3a) => uses_xo_opcodes = true,
+            _ => (),
+        }
+    }
+    (uses_schip_opcodes, uses_xo_opcodes)
+}
@@ -0,0 +1,25 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Whether the virtual machine is executing, and if not why.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RunState {
+    /// `update` executes instructions.
+    #[default]
+    Running,
+    /// Paused by the host, `update` does nothing until it is resumed.
+    Paused,
+    /// The program has finished, by jumping to itself, or hung, see `Event::Finished` and
+    /// `Event::Hung`. It doesn't continue until the virtual machine is reset or a state is loaded.
+    Halted,
+    /// Stopped before executing the instruction at a breakpoint, until it is resumed.
+    Breakpoint,
+    /// Stopped by a stack overflow or underflow, memory reached past its end or an invalid
+    /// opcode, until the virtual machine is reset. See `Event::Fault`.
+    Error,
+}
@@ -0,0 +1,32 @@
+use core::fmt;
+
+/// The slowest speed, in instructions per second.
+pub const MIN_SPEED: u32 = 1;
+/// The fastest speed, in instructions per second: one instruction every microsecond.
+pub const MAX_SPEED: u32 = 1_000_000;
+
+/// Error returned when a speed is outside `MIN_SPEED..=MAX_SPEED`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpeedError {
+    pub speed: u32,
+}
+
+pub(crate) fn check_speed(speed: u32) -> Result<(), SpeedError> {
+    if !(MIN_SPEED..=MAX_SPEED).contains(&speed) {
+        return Err(SpeedError { speed });
+    }
+    Ok(())
+}
+
+impl fmt::Display for SpeedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} instructions per second is outside {} to {}",
+            self.speed, MIN_SPEED, MAX_SPEED
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SpeedError {}
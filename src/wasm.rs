@@ -1,6 +1,7 @@
-use crate::FrameBuffer;
+use crate::alloc::string::ToString;
+use crate::{FrameBuffer, MemoryError, MovieError, RomError, SpeedError, StateError};
 use js_sys::Uint8ClampedArray;
-use wasm_bindgen::{convert::IntoWasmAbi, describe::WasmDescribe};
+use wasm_bindgen::{convert::IntoWasmAbi, describe::WasmDescribe, JsError, JsValue};
 
 impl IntoWasmAbi for FrameBuffer {
     type Abi = <Uint8ClampedArray as IntoWasmAbi>::Abi;
@@ -15,3 +16,33 @@ impl WasmDescribe for FrameBuffer {
         <Uint8ClampedArray as WasmDescribe>::describe();
     }
 }
+
+impl From<MovieError> for JsValue {
+    fn from(error: MovieError) -> Self {
+        JsError::new(&error.to_string()).into()
+    }
+}
+
+impl From<MemoryError> for JsValue {
+    fn from(error: MemoryError) -> Self {
+        JsError::new(&error.to_string()).into()
+    }
+}
+
+impl From<StateError> for JsValue {
+    fn from(error: StateError) -> Self {
+        JsError::new(&error.to_string()).into()
+    }
+}
+
+impl From<SpeedError> for JsValue {
+    fn from(error: SpeedError) -> Self {
+        JsError::new(&error.to_string()).into()
+    }
+}
+
+impl From<RomError> for JsValue {
+    fn from(error: RomError) -> Self {
+        JsError::new(&error.to_string()).into()
+    }
+}